@@ -0,0 +1,110 @@
+// Bitcoin protocol consensus library.
+//
+// SPDX-License-Identifier: Apache-2.0
+//
+// Written in 2019-2024 by
+//     Dr Maxim Orlovsky <orlovsky@lnp-bp.org>
+//
+// Copyright (C) 2019-2024 LNP/BP Standards Association. All rights reserved.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use bech32::{segwit, Fe32, Hrp};
+
+use crate::{OpCode, ScriptPubkey, WitnessVer};
+
+/// Bitcoin network used to select human-readable part of the addresses.
+#[derive(Copy, Clone, Eq, PartialEq, Hash, Debug, Display)]
+#[display(lowercase)]
+pub enum Network {
+    /// Bitcoin mainnet.
+    Mainnet,
+    /// Bitcoin testnet.
+    Testnet,
+    /// Bitcoin signet.
+    Signet,
+    /// Bitcoin regtest.
+    Regtest,
+}
+
+impl Network {
+    /// Returns human-readable part of segwit addresses for the network
+    /// (BIP-173).
+    pub fn bech32_hrp(self) -> Hrp {
+        match self {
+            Network::Mainnet => bech32::hrp::BC,
+            Network::Testnet | Network::Signet => bech32::hrp::TB,
+            Network::Regtest => bech32::hrp::BCRT,
+        }
+    }
+}
+
+impl ScriptPubkey {
+    /// Encodes witness program scriptPubkey as a segwit address: bech32 for
+    /// witness version 0 (BIP-173) and bech32m for later versions, including
+    /// taproot (BIP-350).
+    ///
+    /// Returns `None` if the scriptPubkey is not a witness program.
+    pub fn to_address(&self, network: Network) -> Option<String> {
+        if !self.is_witness_program() {
+            return None;
+        }
+        let op_code = OpCode::try_from(self[0]).ok()?;
+        let version = WitnessVer::from_op_code(op_code).ok()?.version_no();
+        let version = Fe32::try_from(version).ok()?;
+        segwit::encode(network.bech32_hrp(), version, &self[2..]).ok()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use std::str::FromStr;
+
+    use super::*;
+    use crate::OutputPk;
+
+    #[test]
+    fn p2tr_address() {
+        let output_pk =
+            OutputPk::from_str("79be667ef9dcbbac55a06295ce870b07029bfcdb2dce28d959f2815b16f81798")
+                .unwrap();
+        let script_pubkey = ScriptPubkey::p2tr_tweaked(output_pk);
+        assert!(script_pubkey.is_p2tr());
+        assert_eq!(
+            script_pubkey.to_address(Network::Mainnet).unwrap(),
+            "bc1p0xlxvlhemja6c4dqv22uapctqupfhlxm9h8z3k2e72q4k9hcz7vqzk5jj0"
+        );
+        assert_eq!(
+            script_pubkey.to_address(Network::Regtest).unwrap(),
+            "bcrt1p0xlxvlhemja6c4dqv22uapctqupfhlxm9h8z3k2e72q4k9hcz7vqc8gma6"
+        );
+    }
+
+    #[test]
+    fn p2wpkh_address() {
+        let script_pubkey = ScriptPubkey::p2wpkh([
+            0x75, 0x1e, 0x76, 0xe8, 0x19, 0x91, 0x96, 0xd4, 0x54, 0x94, 0x1c, 0x45, 0xd1, 0xb3,
+            0xa3, 0x23, 0xf1, 0x43, 0x3b, 0xd6,
+        ]);
+        assert_eq!(
+            script_pubkey.to_address(Network::Mainnet).unwrap(),
+            "bc1qw508d6qejxtdg4y5r3zarvary0c5xw7kv8f3t4"
+        );
+    }
+
+    #[test]
+    fn non_witness_address() {
+        assert_eq!(ScriptPubkey::p2pkh([0u8; 20]).to_address(Network::Mainnet), None);
+        assert_eq!(ScriptPubkey::op_return(&[0u8; 32]).to_address(Network::Testnet), None);
+    }
+}
@@ -25,10 +25,10 @@ use amplify::confinement::{Confined, MediumBlob, SmallBlob, TinyBlob, U32};
 use amplify::{confinement, ByteArray, Bytes32, IoError, Wrapper};
 
 use crate::{
-    BlockHash, BlockHeader, BlockMerkleRoot, ControlBlock, InternalPk, InvalidLeafVer, LeafVer,
-    LockTime, Outpoint, Parity, RedeemScript, Sats, ScriptBytes, ScriptPubkey, SeqNo, SigScript,
-    TapBranchHash, TapMerklePath, TapScript, Tx, TxIn, TxOut, TxVer, Txid, Vout, Witness,
-    WitnessScript, LIB_NAME_BITCOIN,
+    control_block_first_byte, split_control_byte, BlockHash, BlockHeader, BlockMerkleRoot,
+    ControlBlock, InternalPk, InvalidLeafVer, LockTime, Outpoint, RedeemScript, Sats, ScriptBytes,
+    ScriptPubkey, SeqNo, SigScript, TapBranchHash, TapMerklePath, TapMerklePathError, TapScript,
+    Tx, TxIn, TxOut, TxVer, Txid, Vout, Witness, WitnessScript, LIB_NAME_BITCOIN,
 };
 
 /// Bitcoin consensus allows arrays which length is encoded as VarInt to grow up
@@ -576,8 +576,7 @@ impl ConsensusEncode for ControlBlock {
     fn consensus_encode(&self, writer: &mut impl Write) -> Result<usize, IoError> {
         let mut counter = 1;
 
-        let first_byte =
-            self.leaf_version.to_consensus_u8() & self.output_key_parity.to_consensus_u8();
+        let first_byte = control_block_first_byte(self.leaf_version, self.output_key_parity);
         first_byte.consensus_encode(writer)?;
 
         counter += self.internal_pk.consensus_encode(writer)?;
@@ -592,20 +591,16 @@ impl ConsensusEncode for ControlBlock {
 impl ConsensusDecode for ControlBlock {
     fn consensus_decode(reader: &mut impl Read) -> Result<Self, ConsensusDecodeError> {
         let first_byte = u8::consensus_decode(reader)?;
-        let leaf_version = LeafVer::from_consensus_u8(first_byte & 0xFE)?;
-        let output_key_parity = Parity::from_consensus_u8(first_byte & 0x01).expect("binary value");
+        let (leaf_version, output_key_parity) = split_control_byte(first_byte)?;
 
         let internal_key = InternalPk::consensus_decode(reader)?;
 
         let mut buf = vec![];
         reader.read_to_end(&mut buf)?;
-        let mut iter = buf.chunks_exact(32);
-        let merkle_branch = iter.by_ref().map(TapBranchHash::from_slice_unsafe);
-        let merkle_branch = TapMerklePath::try_from_iter(merkle_branch)
-            .map_err(|_| ConsensusDataError::LongTapMerklePath)?;
-        if !iter.remainder().is_empty() {
-            return Err(ConsensusDataError::InvalidTapMerklePath.into());
-        }
+        let merkle_branch = TapMerklePath::try_from_bytes(&buf).map_err(|err| match err {
+            TapMerklePathError::TooLong(_) => ConsensusDataError::LongTapMerklePath,
+            TapMerklePathError::InvalidLength(_) => ConsensusDataError::InvalidTapMerklePath,
+        })?;
 
         Ok(ControlBlock {
             leaf_version,
@@ -1006,4 +1001,65 @@ mod tests {
         let failure64: Result<u64, _> = deserialize([1u8, 2, 3, 4, 5, 6, 7]);
         assert!(failure64.is_err());
     }
+
+    #[test]
+    fn control_block_future_leaf_ver() {
+        use std::str::FromStr;
+
+        use crate::{LeafVer, Parity};
+
+        let internal_pk = InternalPk::from_str(
+            "c5f93479093e2b8f724a79844cc10928dd44e9a390b539843fb83fbf842723f3",
+        )
+        .unwrap();
+        let mut data = vec![0xc3];
+        data.extend(internal_pk.to_byte_array());
+        data.extend([0xA5; 32]);
+
+        let control_block: ControlBlock = deserialize(&data).unwrap();
+        assert_eq!(control_block.leaf_version, LeafVer::from_consensus_u8(0xc2).unwrap());
+        assert!(matches!(control_block.leaf_version, LeafVer::Future(_)));
+        assert_eq!(control_block.output_key_parity, Parity::Odd);
+        assert_eq!(control_block.internal_pk, internal_pk);
+        assert_eq!(control_block.merkle_branch.len(), 1);
+        assert_eq!(serialize(&control_block), data);
+
+        data[0] = 0x52;
+        let control_block: ControlBlock = deserialize(&data).unwrap();
+        assert_eq!(control_block.leaf_version.to_consensus_u8(), 0x52);
+        assert_eq!(control_block.output_key_parity, Parity::Even);
+        assert_eq!(serialize(&control_block), data);
+
+        // 0x50 is the annex prefix, reserved from being used as a leaf version
+        data[0] = 0x50;
+        assert!(deserialize::<ControlBlock>(&data).is_err());
+    }
+
+    #[test]
+    fn control_block_first_byte_encoding() {
+        use std::str::FromStr;
+
+        use crate::{LeafVer, Parity};
+
+        // Control block from BIP-341 `scriptPubKey` wallet test vectors
+        let internal_pk = InternalPk::from_str(
+            "187791b6f712a8ea41c8ecdd0ee77fab3e85263b37e1ec18a3651926b3a6cf27",
+        )
+        .unwrap();
+        let mut data = vec![0xc1];
+        data.extend(internal_pk.to_byte_array());
+
+        // First byte must combine leaf version and parity bits with OR: using AND
+        // produced 0x00 for odd and even parities alike.
+        let control_block =
+            ControlBlock::with(LeafVer::TapScript, internal_pk, Parity::Odd, none!());
+        assert_eq!(serialize(&control_block), data);
+        assert_eq!(deserialize::<ControlBlock>(&data).unwrap(), control_block);
+
+        data[0] = 0xc0;
+        let control_block =
+            ControlBlock::with(LeafVer::TapScript, internal_pk, Parity::Even, none!());
+        assert_eq!(serialize(&control_block), data);
+        assert_eq!(deserialize::<ControlBlock>(&data).unwrap(), control_block);
+    }
 }
@@ -26,6 +26,30 @@ use crate::{
     CompressedPk, LegacyPk, RedeemScript, UncompressedPk, WitnessScript, LIB_NAME_BITCOIN,
 };
 
+/// Tagged SHA-256 hasher keeping the midstate after processing the tag, such
+/// that hashing multiple messages under the same tag doesn't repeat the tag
+/// processing.
+#[derive(Clone)]
+pub struct TaggedHasher<D: DigestExt + Clone = Sha256>(D);
+
+impl<D: DigestExt + Clone> TaggedHasher<D> {
+    /// Constructs hasher for a given `tag`.
+    pub fn new(tag: impl AsRef<[u8]>) -> Self { Self(D::from_tag(tag)) }
+
+    /// Returns hashing engine initialized with the tag midstate.
+    #[inline]
+    pub fn engine(&self) -> D { self.0.clone() }
+
+    /// Computes tagged hash of a concatenation of the `data` chunks.
+    pub fn hash(&self, data: &[&[u8]]) -> [u8; 32] {
+        let mut engine = self.engine();
+        for chunk in data {
+            engine.input_raw(chunk);
+        }
+        engine.finish()
+    }
+}
+
 #[derive(Wrapper, Copy, Clone, Ord, PartialOrd, Eq, PartialEq, Hash, Debug, From)]
 #[wrapper(Index, RangeOps, AsSlice, BorrowSlice, Hex, Display, FromStr)]
 #[derive(StrictType, StrictDumb, StrictEncode, StrictDecode)]
@@ -161,3 +185,22 @@ impl From<&WitnessScript> for WScriptHash {
         Self(engine2.finish().into())
     }
 }
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn tagged_hasher() {
+        let hasher = TaggedHasher::<Sha256>::new(b"TapBranch");
+        let mut engine = Sha256::from_tag(b"TapBranch");
+        engine.input_raw(&[0xA1; 32]);
+        engine.input_raw(&[0xB2; 32]);
+        let expected = engine.finish();
+        assert_eq!(hasher.hash(&[&[0xA1; 32], &[0xB2; 32]]), expected);
+        assert_eq!(hasher.hash(&[&[0xA1; 32], &[0xB2; 32]]), expected);
+        let data = [[0xA1; 32], [0xB2; 32]].concat();
+        assert_eq!(hasher.hash(&[&data[..10], &data[10..]]), expected);
+        assert_ne!(hasher.hash(&[&data[..63]]), expected);
+    }
+}
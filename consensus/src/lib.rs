@@ -50,6 +50,8 @@ extern crate core;
 /// Re-export of `secp256k1` crate.
 pub extern crate secp256k1;
 
+#[cfg(feature = "bech32")]
+mod address;
 mod block;
 pub mod opcodes;
 mod script;
@@ -66,22 +68,27 @@ mod weights;
 pub mod stl;
 mod coding;
 
+#[cfg(feature = "bech32")]
+pub use address::Network;
 pub use block::{BlockHash, BlockHeader, BlockMerkleRoot};
 pub use coding::{
     ByteStr, ConsensusDataError, ConsensusDecode, ConsensusDecodeError, ConsensusEncode, LenVarInt,
     VarInt, VarIntArray, VarIntBytes,
 };
-pub use hashtypes::{PubkeyHash, ScriptHash, WPubkeyHash, WScriptHash};
+pub use hashtypes::{PubkeyHash, ScriptHash, TaggedHasher, WPubkeyHash, WScriptHash};
 pub use opcodes::OpCode;
 pub use pubkeys::{CompressedPk, InvalidPubkey, LegacyPk, PubkeyParseError, UncompressedPk};
 pub use script::{RedeemScript, ScriptBytes, ScriptPubkey, SigScript};
 pub use segwit::{SegwitError, Witness, WitnessProgram, WitnessScript, WitnessVer, Wtxid};
 pub use sigtypes::{Bip340Sig, LegacySig, SigError, SighashFlag, SighashType};
 pub use taproot::{
-    ControlBlock, FutureLeafVer, InternalPk, IntoTapHash, InvalidLeafVer, InvalidParityValue,
-    LeafScript, LeafVer, OutputPk, Parity, TapBranchHash, TapCode, TapLeafHash, TapMerklePath,
-    TapNodeHash, TapScript, XOnlyPk, MIDSTATE_TAPSIGHASH, TAPROOT_ANNEX_PREFIX, TAPROOT_LEAF_MASK,
-    TAPROOT_LEAF_TAPSCRIPT,
+    classify_taproot_spend, control_block_first_byte, plan_script_spend, split_control_byte,
+    ControlBlock, FutureLeafVer, InternalPk, IntoTapHash, InvalidAnnex, InvalidLeafVer,
+    InvalidParityValue, LeafInfo, LeafScript, LeafVer, OutputPk, Parity, ParityParseError,
+    SpendPlan, TapBranchHash, TapCode, TapLeafHash, TapMerklePath, TapMerklePathError,
+    TapMerklePathShapeError, TapNodeHash, TapScript, TaprootContext, TaprootSpendKind, TapTree,
+    TapTreeError, XOnlyPk, MIDSTATE_TAPSIGHASH, TAPROOT_ANNEX_PREFIX, TAPROOT_LEAF_MASK,
+    TAPROOT_LEAF_TAPSCRIPT, TAPROOT_MAX_DEPTH,
 };
 pub use timelocks::{
     InvalidTimelock, LockHeight, LockTime, LockTimestamp, SeqNo, TimelockParseError,
@@ -262,6 +262,16 @@ impl ScriptBytes {
     #[inline]
     pub(crate) fn push(&mut self, data: u8) { self.0.push(data).expect("script exceeds 4GB") }
 
+    /// Appends raw bytes to the script.
+    ///
+    /// # Errors
+    ///
+    /// If the resulting script length exceeds 4GB.
+    #[inline]
+    pub fn try_extend(&mut self, data: &[u8]) -> Result<(), confinement::Error> {
+        self.0.extend(data.iter().copied())
+    }
+
     #[inline]
     pub(crate) fn extend(&mut self, data: &[u8]) {
         self.0
@@ -330,6 +340,13 @@ mod test {
 
     use super::*;
 
+    #[test]
+    fn is_op_return_short() {
+        assert!(!ScriptPubkey::new().is_op_return());
+        assert!(ScriptPubkey::from_unsafe(vec![OP_RETURN]).is_op_return());
+        assert!(!ScriptPubkey::from_unsafe(vec![OP_PUSHBYTES_40]).is_op_return());
+    }
+
     #[test]
     fn script_index() {
         let mut script = ScriptPubkey::op_return(&[0u8; 40]);
@@ -22,16 +22,18 @@
 #![allow(unused_braces)] // required due to strict dumb derivation and compiler bug
 
 use std::borrow::Borrow;
+use std::collections::BTreeSet;
 use std::fmt::{self, Formatter, LowerHex, UpperHex};
 use std::ops::BitXor;
 use std::str::FromStr;
+use std::sync::OnceLock;
 use std::{cmp, io, slice, vec};
 
 use amplify::confinement::Confined;
 use amplify::hex::FromHex;
-use amplify::{confinement, Bytes32, Wrapper};
-use commit_verify::{DigestExt, Sha256};
-use secp256k1::{PublicKey, Scalar, XOnlyPublicKey};
+use amplify::{confinement, ByteArray, Bytes32, Wrapper};
+use commit_verify::DigestExt;
+use secp256k1::{All, PublicKey, Scalar, Secp256k1, Verification, XOnlyPublicKey};
 use strict_encoding::{
     DecodeError, ReadTuple, StrictDecode, StrictEncode, StrictProduct, StrictTuple, StrictType,
     TypeName, TypedRead, TypedWrite, WriteTuple,
@@ -39,8 +41,8 @@ use strict_encoding::{
 
 use crate::opcodes::*;
 use crate::{
-    CompressedPk, ConsensusEncode, InvalidPubkey, PubkeyParseError, ScriptBytes, ScriptPubkey,
-    WitnessVer, LIB_NAME_BITCOIN,
+    CompressedPk, ConsensusDecode, ConsensusEncode, InvalidPubkey, PubkeyParseError, ScriptBytes,
+    ScriptPubkey, TaggedHasher, Witness, WitnessVer, LIB_NAME_BITCOIN,
 };
 
 /// The SHA-256 midstate value for the TapLeaf hash.
@@ -55,6 +57,21 @@ const MIDSTATE_TAPBRANCH: [u8; 9] = *b"TapBranch";
 const MIDSTATE_TAPTWEAK: [u8; 8] = *b"TapTweak";
 // d129a2f3701c655d6583b6c3b941972795f4e23294fd54f4a2ae8d8547ca590b
 
+fn tap_leaf_hasher() -> &'static TaggedHasher {
+    static HASHER: OnceLock<TaggedHasher> = OnceLock::new();
+    HASHER.get_or_init(TapLeafHash::hasher)
+}
+
+fn tap_branch_hasher() -> &'static TaggedHasher {
+    static HASHER: OnceLock<TaggedHasher> = OnceLock::new();
+    HASHER.get_or_init(TapBranchHash::hasher)
+}
+
+fn tap_tweak_hasher() -> &'static TaggedHasher {
+    static HASHER: OnceLock<TaggedHasher> = OnceLock::new();
+    HASHER.get_or_init(InternalPk::tap_tweak_hasher)
+}
+
 /// The SHA-256 midstate value for the TapSig hash.
 pub const MIDSTATE_TAPSIGHASH: [u8; 10] = *b"TapSighash";
 // f504a425d7f8783b1363868ae3e556586eee945dbc7888dd02a6e2c31873fe9f
@@ -179,25 +196,77 @@ impl InternalPk {
     }
 
     pub fn to_output_pk(&self, merkle_root: Option<impl IntoTapHash>) -> (OutputPk, Parity) {
-        let mut engine = Sha256::from_tag(MIDSTATE_TAPTWEAK);
+        self.to_output_pk_with_root(merkle_root.map(IntoTapHash::into_tap_hash))
+    }
+
+    /// Non-generic version of [`Self::to_output_pk`], which doesn't require
+    /// type annotations when no merkle root is provided.
+    ///
+    /// ```
+    /// # use std::str::FromStr;
+    /// # use bc::{InternalPk, TapNodeHash};
+    /// let internal_pk =
+    ///     InternalPk::from_str("c5f93479093e2b8f724a79844cc10928dd44e9a390b539843fb83fbf842723f3")
+    ///         .unwrap();
+    /// let (key_only, _) = internal_pk.to_output_pk_with_root(None);
+    /// let (scripted, _) = internal_pk.to_output_pk_with_root(Some(TapNodeHash::from([1u8; 32])));
+    /// assert_ne!(key_only, scripted);
+    /// ```
+    pub fn to_output_pk_with_root(&self, merkle_root: Option<TapNodeHash>) -> (OutputPk, Parity) {
+        self.to_output_pk_using(tap_tweak_hasher(), merkle_root)
+    }
+
+    /// Version of [`Self::to_output_pk_with_root`] computing the tweak hash
+    /// with a custom SHA-256 implementation `D`, using `hasher` constructed
+    /// with [`Self::tap_tweak_hasher`].
+    pub fn to_output_pk_using<D: DigestExt + Clone>(
+        &self,
+        hasher: &TaggedHasher<D>,
+        merkle_root: Option<TapNodeHash>,
+    ) -> (OutputPk, Parity) {
+        self.tweak(secp256k1::SECP256K1, hasher, merkle_root)
+    }
+
+    /// Constructs `TapTweak` tagged hasher with a custom SHA-256
+    /// implementation `D`, which can be reused across
+    /// [`Self::to_output_pk_using`] calls.
+    pub fn tap_tweak_hasher<D: DigestExt + Clone>() -> TaggedHasher<D> {
+        TaggedHasher::new(MIDSTATE_TAPTWEAK)
+    }
+
+    /// Computes BIP-341 `TapTweak` scalar, which is added to the internal key
+    /// to produce the output key returned by [`Self::to_output_pk_with_root`].
+    ///
+    /// Signers must add the same scalar to the internal secret key to sign
+    /// for the output key via the key path.
+    pub fn tap_tweak(&self, merkle_root: Option<TapNodeHash>) -> Scalar {
+        self.tap_tweak_using(tap_tweak_hasher(), merkle_root)
+    }
+
+    fn tap_tweak_using<D: DigestExt + Clone>(
+        &self,
+        hasher: &TaggedHasher<D>,
+        merkle_root: Option<TapNodeHash>,
+    ) -> Scalar {
         // always hash the key
-        engine.input_raw(&self.0.serialize());
-        if let Some(merkle_root) = merkle_root {
-            engine.input_raw(merkle_root.into_tap_hash().as_ref());
-        }
-        let tweak =
-            Scalar::from_be_bytes(engine.finish()).expect("hash value greater than curve order");
-        let (output_key, tweaked_parity) = self
-            .0
-            .add_tweak(secp256k1::SECP256K1, &tweak)
-            .expect("hash collision");
-        debug_assert!(self.tweak_add_check(
-            secp256k1::SECP256K1,
-            &output_key,
-            tweaked_parity,
-            tweak
-        ));
-        (OutputPk(XOnlyPk(output_key)), tweaked_parity.into())
+        let key = self.0.serialize();
+        let hash = match merkle_root {
+            Some(merkle_root) => hasher.hash(&[&key, merkle_root.as_ref()]),
+            None => hasher.hash(&[&key]),
+        };
+        Scalar::from_be_bytes(hash).expect("hash value greater than curve order")
+    }
+
+    fn tweak<D: DigestExt + Clone, C: Verification>(
+        &self,
+        secp: &Secp256k1<C>,
+        hasher: &TaggedHasher<D>,
+        merkle_root: Option<TapNodeHash>,
+    ) -> (OutputPk, Parity) {
+        let tweak = self.tap_tweak_using(hasher, merkle_root);
+        let (output_key, tweaked_parity) = self.0.add_tweak(secp, &tweak).expect("hash collision");
+        debug_assert!(self.tweak_add_check(secp, &output_key, tweaked_parity, tweak));
+        (OutputPk(XOnlyPk(output_key)), Parity::from_secp(tweaked_parity))
     }
 }
 
@@ -205,6 +274,79 @@ impl From<InternalPk> for [u8; 32] {
     fn from(pk: InternalPk) -> [u8; 32] { pk.to_byte_array() }
 }
 
+/// Reusable secp256k1 context for taproot key tweaking and verification.
+///
+/// Methods like [`InternalPk::to_output_pk`] use the global secp256k1 context.
+/// Applications performing a lot of taproot verifications may instead create
+/// a context once - possibly randomized with [`TaprootContext::randomized`] -
+/// and perform the operations with its methods.
+///
+/// # Thread safety
+///
+/// The context is [`Send`] and [`Sync`] and its methods take `&self`, thus a
+/// single context can be shared across threads by a reference or inside an
+/// [`std::sync::Arc`] without any locking. Re-randomization requires mutable
+/// access and must be done before sharing the context.
+#[derive(Clone, PartialEq, Eq, Debug)]
+pub struct TaprootContext(Secp256k1<All>);
+
+impl Default for TaprootContext {
+    fn default() -> Self { Self::new() }
+}
+
+impl From<Secp256k1<All>> for TaprootContext {
+    fn from(secp: Secp256k1<All>) -> Self { Self(secp) }
+}
+
+impl TaprootContext {
+    /// Creates new context. The context is randomized only if the
+    /// `secp256k1` crate is compiled with random number generator support;
+    /// use [`Self::randomized`] for explicit randomization.
+    #[inline]
+    pub fn new() -> Self { Self(Secp256k1::new()) }
+
+    /// Creates new context randomized with the provided `seed`, providing
+    /// protection against side-channel attacks.
+    pub fn randomized(seed: &[u8; 32]) -> Self {
+        let mut secp = Secp256k1::new();
+        secp.seeded_randomize(seed);
+        Self(secp)
+    }
+
+    /// Returns reference to the underlying secp256k1 context.
+    #[inline]
+    pub fn as_secp(&self) -> &Secp256k1<All> { &self.0 }
+
+    /// Computes output key and its parity by tweaking the `internal_pk` with
+    /// the `merkle_root`. Equivalent of [`InternalPk::to_output_pk_with_root`]
+    /// using this context.
+    #[inline]
+    pub fn to_output_pk(
+        &self,
+        internal_pk: InternalPk,
+        merkle_root: Option<TapNodeHash>,
+    ) -> (OutputPk, Parity) {
+        internal_pk.tweak(&self.0, tap_tweak_hasher(), merkle_root)
+    }
+
+    /// Verifies that the `control_block` proves inclusion of the `leaf` script
+    /// into the script tree committed to by the `output_pk`. Equivalent of
+    /// [`ControlBlock::verify`] using this context.
+    pub fn verify_control_block(
+        &self,
+        control_block: &ControlBlock,
+        output_pk: OutputPk,
+        leaf: &LeafScript,
+    ) -> bool {
+        if control_block.leaf_version != leaf.version {
+            return false;
+        }
+        let merkle_root = control_block.merkle_root(leaf);
+        let (derived_pk, parity) = self.to_output_pk(control_block.internal_pk, Some(merkle_root));
+        derived_pk == output_pk && parity == control_block.output_key_parity
+    }
+}
+
 /// Output taproot key - an [`InternalPk`] tweaked with merkle root of the
 /// script tree - or its own hash. Used only inside addresses and raw taproot
 /// descriptors.
@@ -271,12 +413,31 @@ impl TapLeafHash {
         Self::with_raw_script(LeafVer::TapScript, tap_script.as_script_bytes())
     }
 
-    fn with_raw_script(version: LeafVer, script: &ScriptBytes) -> Self {
-        let mut engine = Sha256::from_tag(MIDSTATE_TAPLEAF);
-        engine.input_raw(&[version.to_consensus_u8()]);
-        script.len_var_int().consensus_encode(&mut engine).ok();
-        engine.input_raw(script.as_slice());
-        Self(engine.finish().into())
+    /// Computes leaf hash for a script with a given leaf version. This is the
+    /// only entry point for leaf hashing: both [`Self::with_leaf_script`] and
+    /// [`Self::with_tap_script`] use it, and it delegates to
+    /// [`Self::with_raw_script_using`] with a cached [`commit_verify::Sha256`] hasher.
+    #[inline]
+    pub fn with_raw_script(version: LeafVer, script: &ScriptBytes) -> Self {
+        Self::with_raw_script_using(tap_leaf_hasher(), version, script)
+    }
+
+    /// Constructs `TapLeaf` tagged hasher with a custom SHA-256 implementation
+    /// `D`, which can be reused across [`Self::with_raw_script_using`] calls.
+    pub fn hasher<D: DigestExt + Clone>() -> TaggedHasher<D> {
+        TaggedHasher::new(MIDSTATE_TAPLEAF)
+    }
+
+    /// Version of [`Self::with_raw_script`] computing the hash with a custom
+    /// SHA-256 implementation `D`, using `hasher` constructed with
+    /// [`Self::hasher`].
+    pub fn with_raw_script_using<D: DigestExt + Clone>(
+        hasher: &TaggedHasher<D>,
+        version: LeafVer,
+        script: &ScriptBytes,
+    ) -> Self {
+        let len = script.len_var_int().consensus_serialize();
+        Self(hasher.hash(&[&[version.to_consensus_u8()], &len, script.as_slice()]).into())
     }
 }
 
@@ -300,11 +461,28 @@ pub struct TapBranchHash(
 );
 
 impl TapBranchHash {
+    #[inline]
     pub fn with_nodes(node1: TapNodeHash, node2: TapNodeHash) -> Self {
-        let mut engine = Sha256::from_tag(MIDSTATE_TAPBRANCH);
-        engine.input_raw(cmp::min(&node1, &node2).borrow());
-        engine.input_raw(cmp::max(&node1, &node2).borrow());
-        Self(engine.finish().into())
+        Self::with_nodes_using(tap_branch_hasher(), node1, node2)
+    }
+
+    /// Constructs `TapBranch` tagged hasher with a custom SHA-256
+    /// implementation `D`, which can be reused across
+    /// [`Self::with_nodes_using`] calls.
+    pub fn hasher<D: DigestExt + Clone>() -> TaggedHasher<D> {
+        TaggedHasher::new(MIDSTATE_TAPBRANCH)
+    }
+
+    /// Version of [`Self::with_nodes`] computing the hash with a custom SHA-256
+    /// implementation `D`, using `hasher` constructed with [`Self::hasher`].
+    pub fn with_nodes_using<D: DigestExt + Clone>(
+        hasher: &TaggedHasher<D>,
+        node1: TapNodeHash,
+        node2: TapNodeHash,
+    ) -> Self {
+        let min: &[u8] = cmp::min(&node1, &node2).borrow();
+        let max: &[u8] = cmp::max(&node1, &node2).borrow();
+        Self(hasher.hash(&[min, max]).into())
     }
 }
 
@@ -378,6 +556,111 @@ impl TapMerklePath {
     ) -> Result<Self, confinement::Error> {
         Confined::try_from_iter(iter).map(Self::from_inner)
     }
+
+    /// Constructs merkle path from a byte string containing concatenated
+    /// 32-byte hashes, as used in the control block serialization.
+    ///
+    /// # Errors
+    ///
+    /// If the number of hashes exceeds [`TAPROOT_MAX_DEPTH`] or the length of
+    /// the byte string is not a multiple of 32.
+    pub fn try_from_bytes(bytes: &[u8]) -> Result<Self, TapMerklePathError> {
+        let iter = bytes.chunks_exact(32);
+        if iter.len() > TAPROOT_MAX_DEPTH as usize {
+            return Err(TapMerklePathError::TooLong(iter.len()));
+        }
+        if !iter.remainder().is_empty() {
+            return Err(TapMerklePathError::InvalidLength(bytes.len()));
+        }
+        let path = iter.map(TapBranchHash::from_slice_unsafe);
+        Ok(Self::try_from_iter(path).expect("path length is checked"))
+    }
+
+    /// Returns node hashes computed while folding the path from the `leaf` up
+    /// to the merkle root. The first element is the `leaf` hash itself and the
+    /// last one is the merkle root, so the result is never empty.
+    pub fn intermediate_nodes(&self, leaf: TapLeafHash) -> Vec<TapNodeHash> {
+        let mut nodes = Vec::with_capacity(self.len() + 1);
+        let mut node = leaf.into_tap_hash();
+        nodes.push(node);
+        for sibling in self {
+            node = TapBranchHash::with_nodes(node, sibling.into_tap_hash()).into_tap_hash();
+            nodes.push(node);
+        }
+        nodes
+    }
+
+    /// Performs defensive checks of the merkle path shape for the given `leaf`,
+    /// intended for processing untrusted control blocks.
+    ///
+    /// The path is rejected if:
+    /// - a sibling hash is equal to the node it is combined with, i.e. the
+    ///   branch commits to two identical children (including the leaf itself);
+    /// - two adjacent sibling hashes are identical;
+    /// - a node produced by folding the path is equal to the leaf or to some
+    ///   node from a lower level, i.e. the path references itself.
+    ///
+    /// Paths passing the check may still be invalid for a specific output key;
+    /// use [`ControlBlock`] verification for that. Conversely, the rejected
+    /// shapes are not prohibited by consensus rules, but can't be produced by
+    /// a sane script tree construction.
+    ///
+    /// # Errors
+    ///
+    /// With the first detected violation, specifying the index of the sibling
+    /// hash within the path at which it was found.
+    pub fn validate_shape(&self, leaf: TapLeafHash) -> Result<(), TapMerklePathShapeError> {
+        let mut prev: Option<&TapBranchHash> = None;
+        for (index, sibling) in self.iter().enumerate() {
+            if prev == Some(sibling) {
+                return Err(TapMerklePathShapeError::DuplicateSiblings(index));
+            }
+            prev = Some(sibling);
+        }
+
+        let nodes = self.intermediate_nodes(leaf);
+        let mut seen = BTreeSet::new();
+        for (index, (node, sibling)) in nodes.iter().zip(self).enumerate() {
+            if *node == sibling.into_tap_hash() {
+                return Err(TapMerklePathShapeError::SiblingMatchesNode(index));
+            }
+            seen.insert(*node);
+            if seen.contains(&nodes[index + 1]) {
+                return Err(TapMerklePathShapeError::SelfReference(index));
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Errors parsing [`TapMerklePath`] from a byte string.
+#[derive(Copy, Clone, Eq, PartialEq, Hash, Debug, Display, Error)]
+#[display(doc_comments)]
+pub enum TapMerklePathError {
+    /// taproot merkle path contains {0} hashes, exceeding BIP-341 consensus
+    /// limit of 128 elements.
+    TooLong(usize),
+
+    /// taproot merkle path length of {0} bytes is not a multiple of 32.
+    InvalidLength(usize),
+}
+
+/// Degenerate shapes of [`TapMerklePath`] detected by
+/// [`TapMerklePath::validate_shape`].
+#[derive(Copy, Clone, Eq, PartialEq, Hash, Debug, Display, Error)]
+#[display(doc_comments)]
+pub enum TapMerklePathShapeError {
+    /// taproot merkle path contains identical adjacent sibling hashes at
+    /// position {0}.
+    DuplicateSiblings(usize),
+
+    /// taproot merkle path sibling hash at position {0} is equal to the node
+    /// it is combined with.
+    SiblingMatchesNode(usize),
+
+    /// folding taproot merkle path at position {0} produces a node already
+    /// present at a lower level of the path.
+    SelfReference(usize),
 }
 
 /// Taproot annex prefix.
@@ -429,12 +712,15 @@ impl StrictDecode for LeafVer {
     fn strict_decode(reader: &mut impl TypedRead) -> Result<Self, DecodeError> {
         reader.read_tuple(|r| {
             let version = r.read_field()?;
-            Self::from_consensus_u8(version)
-                .map_err(|err| DecodeError::DataIntegrityError(err.to_string()))
+            Self::from_consensus_u8(version).map_err(DecodeError::from)
         })
     }
 }
 
+impl From<InvalidLeafVer> for DecodeError {
+    fn from(err: InvalidLeafVer) -> Self { DecodeError::DataIntegrityError(err.to_string()) }
+}
+
 impl LeafVer {
     #[doc(hidden)]
     #[deprecated(since = "0.10.9", note = "use from_consensus_u8")]
@@ -489,7 +775,7 @@ impl UpperHex for LeafVer {
 #[cfg_attr(
     feature = "serde",
     derive(Serialize, Deserialize),
-    serde(crate = "serde_crate", transparent)
+    serde(crate = "serde_crate", try_from = "u8", into = "u8")
 )]
 pub struct FutureLeafVer(u8);
 
@@ -510,6 +796,22 @@ impl FutureLeafVer {
     pub fn to_consensus(self) -> u8 { self.0 }
 }
 
+impl TryFrom<u8> for FutureLeafVer {
+    type Error = InvalidLeafVer;
+
+    fn try_from(version: u8) -> Result<Self, Self::Error> {
+        match version {
+            TAPROOT_LEAF_TAPSCRIPT => Err(InvalidLeafVer(TAPROOT_LEAF_TAPSCRIPT)),
+            future => Self::from_consensus(future),
+        }
+    }
+}
+
+impl From<FutureLeafVer> for u8 {
+    #[inline]
+    fn from(version: FutureLeafVer) -> Self { version.0 }
+}
+
 impl LowerHex for FutureLeafVer {
     #[inline]
     fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result { LowerHex::fmt(&self.0, f) }
@@ -557,6 +859,15 @@ impl LeafScript {
     #[inline]
     pub fn as_script_bytes(&self) -> &ScriptBytes { &self.script }
 
+    /// Returns tapscript contained in the leaf if the leaf version is
+    /// [`LeafVer::TapScript`], or `None` for future leaf versions.
+    pub fn as_tap_script(&self) -> Option<TapScript> {
+        match self.version {
+            LeafVer::TapScript => Some(TapScript::from(self.script.clone())),
+            LeafVer::Future(_) => None,
+        }
+    }
+
     #[inline]
     pub fn tap_leaf_hash(&self) -> TapLeafHash { TapLeafHash::with_leaf_script(self) }
 }
@@ -593,6 +904,11 @@ pub enum TapCode {
     PushData4 = OP_PUSHDATA4,
 }
 
+/// Tapscript code.
+///
+/// With serde, the script is serialized as a hex string for human-readable
+/// formats (like JSON) and as a byte string otherwise, matching the
+/// [`ScriptBytes`] serialization.
 #[derive(Wrapper, WrapperMut, Clone, Ord, PartialOrd, Eq, PartialEq, Hash, Debug, From, Default)]
 #[wrapper(Deref, AsSlice, Hex)]
 #[wrapper_mut(DerefMut, AsSliceMut)]
@@ -636,10 +952,159 @@ impl TapScript {
     #[inline]
     pub fn push_opcode(&mut self, op_code: TapCode) { self.0.push(op_code as u8); }
 
+    /// Appends another script to the end of this script.
+    ///
+    /// # Errors
+    ///
+    /// If the resulting script length exceeds 4GB.
+    #[inline]
+    pub fn extend_from(&mut self, other: &TapScript) -> Result<(), confinement::Error> {
+        self.0.try_extend(other.as_slice())
+    }
+
+    /// Adds a single opcode to the script, returning the script back.
+    #[inline]
+    pub fn with_op(mut self, op_code: TapCode) -> Self {
+        self.push_opcode(op_code);
+        self
+    }
+
+    /// Adds instructions to push some arbitrary data onto the stack, returning
+    /// the script back.
+    ///
+    /// # Panics
+    ///
+    /// If the resulting script length exceeds 4GB.
+    #[inline]
+    pub fn with_slice(mut self, data: &[u8]) -> Self {
+        self.0.push_slice(data);
+        self
+    }
+
+    /// Appends another script to the end of this script, returning the script
+    /// back.
+    ///
+    /// # Errors
+    ///
+    /// If the resulting script length exceeds 4GB.
+    #[inline]
+    pub fn with_script(mut self, other: &TapScript) -> Result<Self, confinement::Error> {
+        self.extend_from(other)?;
+        Ok(self)
+    }
+
+    /// Checks that all data pushes in the script use the shortest possible
+    /// push opcode (the MINIMALDATA standardness rule). Non-minimal pushes
+    /// don't change script semantics, but do change the leaf hash.
+    ///
+    /// Scripts with a push running past the end of the script are reported as
+    /// not minimally encoded.
+    pub fn is_minimally_encoded(&self) -> bool {
+        self.instructions().all(|instruction| match instruction {
+            Err(()) => false,
+            Ok((op_code, _)) if !(OP_PUSHBYTES_1..=OP_PUSHDATA4).contains(&op_code) => true,
+            // must use OP_PUSHNUM_1 - OP_PUSHNUM_16 or OP_PUSHNUM_NEG1
+            Ok((_, [1..=16] | [0x81])) => false,
+            Ok((op_code, data)) if data.len() <= OP_PUSHBYTES_75 as usize => {
+                op_code <= OP_PUSHBYTES_75
+            }
+            Ok((op_code, data)) if data.len() <= 0xFF => op_code == OP_PUSHDATA1,
+            Ok((op_code, data)) if data.len() <= 0xFFFF => op_code == OP_PUSHDATA2,
+            Ok(_) => true,
+        })
+    }
+
+    /// Detects scripts starting with `OP_RETURN`, which makes any attempt to
+    /// spend them fail.
+    ///
+    /// In tapscript `OP_SUCCESSx` op codes (BIP-342) make the script succeed
+    /// once encountered while decoding, before any execution happens. Thus a
+    /// script starting with `OP_RETURN` but containing any `OP_SUCCESSx` op
+    /// code (outside of the pushed data) is not reported as unspendable.
+    ///
+    /// NB: For this reason tapret commitment leafs, which start with 29
+    /// `OP_RESERVED` (`OP_SUCCESS80` in tapscript) op codes, are NOT provably
+    /// unspendable. Their protection relies on the leaf script being known only
+    /// to the parties having the tapret proof.
+    pub fn is_provably_unspendable(&self) -> bool {
+        if self.as_slice().first() != Some(&OP_RETURN) {
+            return false;
+        }
+        for instruction in self.instructions() {
+            match instruction {
+                // script with a truncated push fails to decode
+                Err(()) => return true,
+                Ok((op_code, _)) if is_op_success(op_code) => return false,
+                Ok(_) => {}
+            }
+        }
+        true
+    }
+
+    /// Iterates over script instructions, returning the op code together with
+    /// the pushed data (empty for non-push op codes). Yields a single error
+    /// and stops if a push runs past the end of the script.
+    fn instructions(&self) -> Instructions {
+        Instructions {
+            script: self.as_slice(),
+            pos: 0,
+        }
+    }
+
     #[inline]
     pub fn as_script_bytes(&self) -> &ScriptBytes { &self.0 }
 }
 
+/// Checks whether the op code is one of `OP_SUCCESSx` op codes defined by
+/// BIP-342.
+fn is_op_success(op_code: u8) -> bool {
+    matches!(
+        op_code,
+        80 | 98 | 126..=129 | 131..=134 | 137..=138 | 141..=142 | 149..=153 | 187..=254
+    )
+}
+
+struct Instructions<'script> {
+    script: &'script [u8],
+    pos: usize,
+}
+
+impl<'script> Iterator for Instructions<'script> {
+    type Item = Result<(u8, &'script [u8]), ()>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let script = self.script;
+        let op_code = *script.get(self.pos)?;
+        self.pos += 1;
+        let len_size = match op_code {
+            OP_PUSHBYTES_1..=OP_PUSHBYTES_75 => 0,
+            OP_PUSHDATA1 => 1,
+            OP_PUSHDATA2 => 2,
+            OP_PUSHDATA4 => 4,
+            _ => return Some(Ok((op_code, &[]))),
+        };
+        let pos = self.pos;
+        let Some(len_bytes) = script.get(pos..pos.saturating_add(len_size)) else {
+            self.pos = script.len();
+            return Some(Err(()));
+        };
+        let len = match op_code {
+            OP_PUSHBYTES_1..=OP_PUSHBYTES_75 => op_code as usize,
+            _ => len_bytes
+                .iter()
+                .rev()
+                .fold(0usize, |len, byte| (len << 8) | *byte as usize),
+        };
+        let start = pos + len_size;
+        let Some(data) = script.get(start..start.saturating_add(len)) else {
+            self.pos = script.len();
+            return Some(Err(()));
+        };
+        self.pos = start + len;
+        Some(Ok((op_code, data)))
+    }
+}
+
 impl ScriptPubkey {
     pub fn p2tr(internal_key: InternalPk, merkle_root: Option<impl IntoTapHash>) -> Self {
         let (output_key, _) = internal_key.to_output_pk(merkle_root);
@@ -647,7 +1112,7 @@ impl ScriptPubkey {
     }
 
     pub fn p2tr_key_only(internal_key: InternalPk) -> Self {
-        let (output_key, _) = internal_key.to_output_pk(None::<TapNodeHash>);
+        let (output_key, _) = internal_key.to_output_pk_with_root(None);
         Self::p2tr_tweaked(output_key)
     }
 
@@ -672,6 +1137,18 @@ impl ScriptPubkey {
 #[display(doc_comments)]
 pub struct InvalidParityValue(pub u8);
 
+/// Errors parsing [`Parity`] from a string.
+#[derive(Clone, Eq, PartialEq, Debug, Display, From, Error)]
+#[display(doc_comments)]
+pub enum ParityParseError {
+    /// {0}
+    #[from]
+    InvalidValue(InvalidParityValue),
+
+    /// invalid parity '{0}' - must be 'even', 'odd', 0 or 1
+    InvalidString(String),
+}
+
 /// Represents the parity passed between FFI function calls.
 #[derive(Copy, Clone, PartialEq, Eq, PartialOrd, Ord, Hash, Debug, Display)]
 #[display(lowercase)]
@@ -692,15 +1169,49 @@ pub enum Parity {
 }
 
 impl From<secp256k1::Parity> for Parity {
-    fn from(parity: secp256k1::Parity) -> Self {
+    #[inline]
+    fn from(parity: secp256k1::Parity) -> Self { Parity::from_secp(parity) }
+}
+
+impl From<Parity> for secp256k1::Parity {
+    #[inline]
+    fn from(parity: Parity) -> Self { parity.to_secp() }
+}
+
+impl FromStr for Parity {
+    type Err = ParityParseError;
+
+    /// Parses parity from `even`/`odd` strings (case-insensitive) or from its
+    /// consensus value (`0` or `1`).
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_lowercase().as_str() {
+            "even" => Ok(Parity::Even),
+            "odd" => Ok(Parity::Odd),
+            other => match other.parse::<u8>() {
+                Ok(value) => Ok(Parity::from_consensus_u8(value)?),
+                Err(_) => Err(ParityParseError::InvalidString(s.to_owned())),
+            },
+        }
+    }
+}
+
+impl Parity {
+    /// Converts [`secp256k1::Parity`] into [`Parity`].
+    pub fn from_secp(parity: secp256k1::Parity) -> Parity {
         match parity {
             secp256k1::Parity::Even => Parity::Even,
             secp256k1::Parity::Odd => Parity::Odd,
         }
     }
-}
 
-impl Parity {
+    /// Converts parity into [`secp256k1::Parity`].
+    pub fn to_secp(self) -> secp256k1::Parity {
+        match self {
+            Parity::Even => secp256k1::Parity::Even,
+            Parity::Odd => secp256k1::Parity::Odd,
+        }
+    }
+
     /// Converts parity into an integer (byte) value.
     ///
     /// This returns `0` for even parity and `1` for odd parity.
@@ -769,4 +1280,1266 @@ impl ControlBlock {
             merkle_branch,
         }
     }
+
+    /// Constructs witness for a script path spending by appending the leaf
+    /// script and the serialized control block to the provided `stack` of
+    /// witness items satisfying the script (signatures etc).
+    ///
+    /// The leaf version of the control block must match the version of the
+    /// `leaf`.
+    #[inline]
+    pub fn into_witness(self, leaf: &LeafScript, stack: Vec<Vec<u8>>) -> Witness {
+        self.witness_stack(leaf, stack, None)
+    }
+
+    /// Constructs witness for a script path spending by appending the leaf
+    /// script, the serialized control block and the annex to the provided
+    /// `stack` of witness items satisfying the script (signatures etc).
+    ///
+    /// The leaf version of the control block must match the version of the
+    /// `leaf`.
+    ///
+    /// # Errors
+    ///
+    /// If the annex doesn't start with [`TAPROOT_ANNEX_PREFIX`].
+    pub fn into_witness_with_annex(
+        self,
+        leaf: &LeafScript,
+        stack: Vec<Vec<u8>>,
+        annex: Vec<u8>,
+    ) -> Result<Witness, InvalidAnnex> {
+        match annex.first() {
+            Some(&TAPROOT_ANNEX_PREFIX) => Ok(self.witness_stack(leaf, stack, Some(annex))),
+            first => Err(InvalidAnnex(first.copied())),
+        }
+    }
+
+    fn witness_stack(
+        self,
+        leaf: &LeafScript,
+        mut stack: Vec<Vec<u8>>,
+        annex: Option<Vec<u8>>,
+    ) -> Witness {
+        stack.push(leaf.script.clone().into_vec());
+        stack.push(self.consensus_serialize());
+        stack.extend(annex);
+        Witness::from_consensus_stack(stack)
+    }
+
+    /// Verifies that the control block proves inclusion of the `leaf` script
+    /// into the script tree committed to by the `output_pk`, i.e. that the
+    /// script path spending with this control block is valid.
+    pub fn verify(&self, output_pk: OutputPk, leaf: &LeafScript) -> bool {
+        if self.leaf_version != leaf.version {
+            return false;
+        }
+        let (derived_pk, parity) = self.derived_output_pk(leaf);
+        derived_pk == output_pk && parity == self.output_key_parity
+    }
+
+    /// Returns internal key of the taproot output.
+    #[inline]
+    pub fn internal_pk(&self) -> InternalPk { self.internal_pk }
+
+    /// Returns short human-readable summary of the control block for logging,
+    /// in form of `ctrl[v=c0 parity=odd ik=c5f93479.. depth=3]`, containing
+    /// leaf version, output key parity, first 8 hex digits of the internal key
+    /// and the depth of the merkle branch.
+    pub fn summary(&self) -> String {
+        format!(
+            "ctrl[v={:02x} parity={} ik={}.. depth={}]",
+            self.leaf_version.to_consensus_u8(),
+            self.output_key_parity,
+            &self.internal_pk.to_string()[..8],
+            self.merkle_branch.len()
+        )
+    }
+
+    /// Derives output key and its parity from the internal key and the merkle
+    /// root computed by folding the merkle branch of the control block with
+    /// the `leaf` script.
+    ///
+    /// Unlike [`Self::verify`], doesn't check leaf version and parity, allowing
+    /// to compare the derived key against multiple candidates.
+    pub fn derived_output_pk(&self, leaf: &LeafScript) -> (OutputPk, Parity) {
+        self.internal_pk.to_output_pk_with_root(Some(self.merkle_root(leaf)))
+    }
+
+    fn merkle_root(&self, leaf: &LeafScript) -> TapNodeHash {
+        self.merkle_branch
+            .iter()
+            .fold(leaf.tap_leaf_hash().into_tap_hash(), |node, sibling| {
+                TapBranchHash::with_nodes(node, sibling.into_tap_hash()).into_tap_hash()
+            })
+    }
+}
+
+/// taproot annex must be non-empty and start with 0x50 byte.
+#[derive(Copy, Clone, Eq, PartialEq, Hash, Debug, Display, Error)]
+#[display(doc_comments)]
+pub struct InvalidAnnex(pub Option<u8>);
+
+/// Computes the first byte of a serialized control block, combining the leaf
+/// `version` with the output key `parity`.
+#[inline]
+pub fn control_block_first_byte(version: LeafVer, parity: Parity) -> u8 {
+    version.to_consensus_u8() | parity.to_consensus_u8()
+}
+
+/// Splits the first byte of a serialized control block into the leaf version
+/// and the output key parity.
+///
+/// # Errors
+///
+/// If the leaf version is invalid, i.e. equal to [`TAPROOT_ANNEX_PREFIX`].
+pub fn split_control_byte(byte: u8) -> Result<(LeafVer, Parity), InvalidLeafVer> {
+    let version = LeafVer::from_consensus_u8(byte & TAPROOT_LEAF_MASK)?;
+    let parity = Parity::from_consensus_u8(byte & !TAPROOT_LEAF_MASK).expect("binary value");
+    Ok((version, parity))
+}
+
+/// Data required for signing and constructing witness for a taproot script
+/// path spending.
+#[derive(Clone, Eq, PartialEq, Hash, Debug)]
+pub struct SpendPlan {
+    /// Control block for the spent leaf.
+    pub control_block: ControlBlock,
+    /// Spent leaf script.
+    pub leaf_script: LeafScript,
+    /// Hash of the spent leaf, used in signature hash computation.
+    pub leaf_hash: TapLeafHash,
+    /// Merkle root of the script tree.
+    pub merkle_root: TapNodeHash,
+    /// Output key of the spent output.
+    pub output_pk: OutputPk,
+    /// Parity of the output key.
+    pub output_parity: Parity,
+}
+
+/// Prepares script path spending of the `leaf` from the output with the
+/// `internal_pk` and the script `tree`. Returns `None` if the tree doesn't
+/// contain the leaf.
+pub fn plan_script_spend(
+    internal_pk: InternalPk,
+    tree: &TapTree,
+    leaf: &LeafScript,
+) -> Option<SpendPlan> {
+    let merkle_branch = tree.merkle_path(leaf)?;
+    let merkle_root = tree.merkle_root();
+    let (output_pk, output_parity) = internal_pk.to_output_pk(Some(merkle_root));
+    Some(SpendPlan {
+        control_block: ControlBlock::with(leaf.version, internal_pk, output_parity, merkle_branch),
+        leaf_script: leaf.clone(),
+        leaf_hash: leaf.tap_leaf_hash(),
+        merkle_root,
+        output_pk,
+        output_parity,
+    })
+}
+
+/// Kind of taproot output spending, detected from the input witness.
+#[derive(Clone, Eq, PartialEq, Hash, Debug)]
+pub enum TaprootSpendKind {
+    /// Key path spending with a single Schnorr signature.
+    KeyPath,
+
+    /// Script path spending of the leaf script with the control block.
+    ScriptPath {
+        /// Control block proving inclusion of the leaf script.
+        control_block: ControlBlock,
+        /// Leaf script which is spent.
+        leaf_script: LeafScript,
+    },
+
+    /// Witness doesn't match any of the taproot spending paths.
+    Malformed,
+}
+
+/// Detects whether the `witness` of a taproot input spends through the key
+/// path or the script path, following BIP-341 witness structure rules.
+///
+/// The annex, if present as the last witness element starting with the
+/// [`TAPROOT_ANNEX_PREFIX`], is ignored.
+pub fn classify_taproot_spend(witness: &Witness) -> TaprootSpendKind {
+    let mut elements = witness.elements().collect::<Vec<_>>();
+    if elements.len() >= 2 &&
+        elements.last().and_then(|el| el.first()) == Some(&TAPROOT_ANNEX_PREFIX)
+    {
+        elements.pop();
+    }
+    match elements.as_slice() {
+        [] => TaprootSpendKind::Malformed,
+        [sig] if sig.len() == 64 || sig.len() == 65 => TaprootSpendKind::KeyPath,
+        [_] => TaprootSpendKind::Malformed,
+        [.., script, control_block] => {
+            let Ok(control_block) = ControlBlock::consensus_deserialize(control_block) else {
+                return TaprootSpendKind::Malformed;
+            };
+            match LeafScript::with_bytes(control_block.leaf_version, script.to_vec()) {
+                Ok(leaf_script) => TaprootSpendKind::ScriptPath {
+                    control_block,
+                    leaf_script,
+                },
+                Err(_) => TaprootSpendKind::Malformed,
+            }
+        }
+    }
+}
+
+/// Maximal depth of the taproot script tree allowed by BIP-341.
+pub const TAPROOT_MAX_DEPTH: u8 = 128;
+
+/// Errors constructing [`TapTree`].
+#[derive(Copy, Clone, Eq, PartialEq, Hash, Debug, Display, Error)]
+#[display(doc_comments)]
+pub enum TapTreeError {
+    /// taproot script tree must contain at least one leaf.
+    NoLeaves,
+
+    /// taproot script tree depth exceeds the limit of 128 levels.
+    MaxDepthExceeded,
+
+    /// leaf depths provided for the taproot script tree do not form a complete
+    /// binary tree.
+    IncompleteTree,
+}
+
+/// Leaf of a taproot script tree annotated with its depth.
+#[derive(Clone, Eq, PartialEq, Hash, Debug)]
+pub struct LeafInfo {
+    /// Depth of the leaf inside the tree; zero if the leaf is the tree root.
+    pub depth: u8,
+    /// Leaf script.
+    pub script: LeafScript,
+}
+
+impl LeafInfo {
+    #[inline]
+    pub fn with(depth: u8, script: LeafScript) -> Self { LeafInfo { depth, script } }
+}
+
+/// Taproot script tree, represented by the list of its leaves in depth-first
+/// order, each annotated with its depth (the same representation is used by
+/// BIP-371 `PSBT_OUT_TAP_TREE`).
+///
+/// All tree operations, including merkle root computation, are iterative and
+/// do not recurse, so their stack usage doesn't depend on the tree depth.
+#[derive(Clone, Eq, PartialEq, Hash, Debug)]
+pub struct TapTree(Vec<LeafInfo>);
+
+impl TapTree {
+    /// Constructs tree from the leaves listed in depth-first order.
+    ///
+    /// # Errors
+    ///
+    /// If there are no leaves, a leaf depth exceeds [`TAPROOT_MAX_DEPTH`] or
+    /// the depths do not form a complete binary tree.
+    pub fn with_leaves(leaves: impl IntoIterator<Item = LeafInfo>) -> Result<Self, TapTreeError> {
+        let leaves = leaves.into_iter().collect::<Vec<_>>();
+        Self::fold(&leaves, None)?;
+        Ok(TapTree(leaves))
+    }
+
+    /// Constructs a tree of a minimal depth from a list of leaf scripts,
+    /// keeping their order. A single script produces a tree consisting of one
+    /// leaf at depth zero; two scripts produce a single branch.
+    pub fn balanced(scripts: impl IntoIterator<Item = LeafScript>) -> Result<Self, TapTreeError> {
+        let scripts = scripts.into_iter().collect::<Vec<_>>();
+        if scripts.is_empty() {
+            return Err(TapTreeError::NoLeaves);
+        }
+        let mut depths = Vec::with_capacity(scripts.len());
+        // Each stack item is a number of leaves in a subtree and the subtree
+        // depth; the left subtree gets the larger half of the leaves.
+        let mut stack = vec![(scripts.len(), 0u8)];
+        while let Some((count, depth)) = stack.pop() {
+            if count == 1 {
+                depths.push(depth);
+                continue;
+            }
+            stack.push((count / 2, depth + 1));
+            stack.push((count - count / 2, depth + 1));
+        }
+        Self::with_leaves(depths.into_iter().zip(scripts).map(|(depth, script)| LeafInfo {
+            depth,
+            script,
+        }))
+    }
+
+    /// Returns iterator over tree leaves in depth-first order.
+    #[inline]
+    pub fn leaves(&self) -> slice::Iter<LeafInfo> { self.0.iter() }
+
+    /// Releases tree leaves in depth-first order.
+    #[inline]
+    pub fn into_leaves(self) -> Vec<LeafInfo> { self.0 }
+
+    /// Splits the tree into two subtrees, rooted at the children of the tree
+    /// root. Returns `None` if the tree consists of a single leaf.
+    pub fn split(&self) -> Option<(TapTree, TapTree)> {
+        if self.0.len() == 1 {
+            return None;
+        }
+        // Each leaf at depth `d` takes 2^(128-d) "slots" of the tree; the left
+        // subtree is complete when it takes half of all slots.
+        let half = 1u128 << (TAPROOT_MAX_DEPTH - 1);
+        let mut weight = 0u128;
+        let mut pos = 0usize;
+        while weight < half {
+            weight += 1u128 << (TAPROOT_MAX_DEPTH - self.0[pos].depth);
+            pos += 1;
+        }
+        let subtree = |leaves: &[LeafInfo]| {
+            TapTree(
+                leaves
+                    .iter()
+                    .map(|leaf| LeafInfo::with(leaf.depth - 1, leaf.script.clone()))
+                    .collect(),
+            )
+        };
+        Some((subtree(&self.0[..pos]), subtree(&self.0[pos..])))
+    }
+
+    /// Lists all x-only keys which may be used for spending through the
+    /// script path, for wallet auditing purposes.
+    ///
+    /// This is a best-effort static analysis: it collects valid 32-byte keys
+    /// pushed right before `OP_CHECKSIG`, `OP_CHECKSIGVERIFY` or
+    /// `OP_CHECKSIGADD` in tapscript leaves. Keys provided by the witness or
+    /// assembled during script execution, as well as leaves with future leaf
+    /// versions, are not covered.
+    pub fn spending_keys(&self) -> BTreeSet<XOnlyPk> {
+        let mut keys = BTreeSet::new();
+        for leaf in &self.0 {
+            let Some(script) = leaf.script.as_tap_script() else {
+                continue;
+            };
+            let mut last_push = None;
+            for instruction in script.instructions() {
+                let Ok((op_code, data)) = instruction else {
+                    break;
+                };
+                if let (OP_CHECKSIG | OP_CHECKSIGVERIFY | OP_CHECKSIGADD, Some(data)) =
+                    (op_code, last_push)
+                {
+                    keys.extend(XOnlyPk::from_bytes(data).ok());
+                }
+                last_push = Some(data).filter(|_| op_code == OP_PUSHBYTES_32);
+            }
+        }
+        keys
+    }
+
+    /// Computes merkle root of the tree.
+    pub fn merkle_root(&self) -> TapNodeHash {
+        let (root, _) =
+            Self::fold(&self.0, None).expect("tree structure is validated during construction");
+        root
+    }
+
+    /// Constructs merkle path for the first leaf in the tree matching the
+    /// provided leaf script. Returns `None` if the tree doesn't contain the
+    /// leaf script.
+    pub fn merkle_path(&self, leaf_script: &LeafScript) -> Option<TapMerklePath> {
+        let pos = self.0.iter().position(|leaf| &leaf.script == leaf_script)?;
+        let (_, path) = Self::fold(&self.0, Some(pos))
+            .expect("tree structure is validated during construction");
+        Some(TapMerklePath::try_from(path).expect("tree depth never exceeds 128"))
+    }
+
+    // Iterative merkle root computation, using heap-allocated stack bounded by
+    // the maximal tree depth. If `target` leaf index is given, also collects
+    // the merkle path for that leaf.
+    fn fold(
+        leaves: &[LeafInfo],
+        target: Option<usize>,
+    ) -> Result<(TapNodeHash, Vec<TapBranchHash>), TapTreeError> {
+        // Stack of subtrees lacking their right sibling; their depths are
+        // strictly increasing towards the top. The flag indicates whether a
+        // subtree contains the target leaf.
+        let mut stack =
+            Vec::<(u8, TapNodeHash, bool)>::with_capacity(TAPROOT_MAX_DEPTH as usize + 1);
+        let mut path = vec![];
+        for (pos, leaf) in leaves.iter().enumerate() {
+            if leaf.depth > TAPROOT_MAX_DEPTH {
+                return Err(TapTreeError::MaxDepthExceeded);
+            }
+            let mut depth = leaf.depth;
+            let mut node = leaf.script.tap_leaf_hash().into_tap_hash();
+            let mut is_target = target == Some(pos);
+            while let Some((sibling_depth, sibling, sibling_is_target)) = stack.last().copied() {
+                if sibling_depth != depth {
+                    break;
+                }
+                if depth == 0 {
+                    return Err(TapTreeError::IncompleteTree);
+                }
+                stack.pop();
+                if is_target {
+                    path.push(TapBranchHash::from_inner(sibling.into_inner()));
+                } else if sibling_is_target {
+                    path.push(TapBranchHash::from_inner(node.into_inner()));
+                }
+                node = TapBranchHash::with_nodes(sibling, node).into_tap_hash();
+                is_target |= sibling_is_target;
+                depth -= 1;
+            }
+            if matches!(stack.last(), Some((sibling_depth, ..)) if *sibling_depth > depth) {
+                return Err(TapTreeError::IncompleteTree);
+            }
+            stack.push((depth, node, is_target));
+        }
+        match stack.as_slice() {
+            [] => Err(TapTreeError::NoLeaves),
+            [(0, root, _)] => Ok((*root, path)),
+            _ => Err(TapTreeError::IncompleteTree),
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use commit_verify::Sha256;
+    use strict_encoding::StrictReader;
+
+    use super::*;
+
+    fn scripts(count: u8) -> Vec<LeafScript> {
+        (0..count)
+            .map(|no| LeafScript::from_tap_script(TapScript::from_unsafe(vec![OP_PUSHNUM_1 + no])))
+            .collect()
+    }
+
+    fn internal_pk() -> InternalPk {
+        InternalPk::from_str("c5f93479093e2b8f724a79844cc10928dd44e9a390b539843fb83fbf842723f3")
+            .unwrap()
+    }
+
+    fn depths(tree: &TapTree) -> Vec<u8> { tree.leaves().map(|leaf| leaf.depth).collect() }
+
+    fn hash_of(value: &impl std::hash::Hash) -> u64 {
+        use std::hash::Hasher;
+        let mut hasher = std::collections::hash_map::DefaultHasher::new();
+        value.hash(&mut hasher);
+        hasher.finish()
+    }
+
+    fn assert_constructors_consistent<T>()
+    where
+        T: From<[u8; 32]> + From<Bytes32> + FromStr + FromHex + fmt::Display,
+        T: std::hash::Hash + Eq + fmt::Debug,
+        <T as FromStr>::Err: fmt::Debug,
+    {
+        let bytes: [u8; 32] = core::array::from_fn(|i| i as u8);
+        let hex = "000102030405060708090a0b0c0d0e0f101112131415161718191a1b1c1d1e1f";
+
+        let values = [
+            T::from(bytes),
+            T::from(Bytes32::from(bytes)),
+            T::from_str(hex).unwrap(),
+            T::from_hex(hex).unwrap(),
+        ];
+        for value in &values {
+            assert_eq!(value, &values[0]);
+            assert_eq!(hash_of(value), hash_of(&values[0]));
+            assert_eq!(value.to_string(), hex);
+        }
+
+        let mut other = bytes;
+        other[31] ^= 1;
+        assert_ne!(T::from(other), values[0]);
+    }
+
+    #[test]
+    fn leaf_script_as_tap_script() {
+        let tap_script = TapScript::from_unsafe(vec![OP_PUSHNUM_1]);
+        let leaf_script = LeafScript::from_tap_script(tap_script.clone());
+        assert_eq!(leaf_script.as_tap_script(), Some(tap_script));
+
+        let future = LeafVer::from_consensus_u8(0xc2).unwrap();
+        let leaf_script = LeafScript::with_bytes(future, vec![OP_PUSHNUM_1]).unwrap();
+        assert_eq!(leaf_script.as_tap_script(), None);
+    }
+
+    const LEAF_VER_BOUNDARIES: [u8; 8] = [0x00, 0x50, 0x51, 0x52, 0xBF, 0xC0, 0xC1, 0xFE];
+
+    #[test]
+    fn leaf_ver_strict_decode_boundaries() {
+        for byte in LEAF_VER_BOUNDARIES {
+            let mut reader = StrictReader::in_memory::<1>([byte]);
+            assert_eq!(
+                LeafVer::strict_decode(&mut reader).ok(),
+                LeafVer::from_consensus_u8(byte).ok(),
+                "strict decoding of leaf version {byte:#04x}"
+            );
+        }
+    }
+
+    #[test]
+    fn strict_decode_truncated_vs_invalid() {
+        fn is_truncated(err: &DecodeError) -> bool {
+            matches!(err, DecodeError::Io(err) if err.kind() == io::ErrorKind::UnexpectedEof)
+        }
+        fn is_invalid(err: &DecodeError, prefix: &str) -> bool {
+            matches!(err, DecodeError::DataIntegrityError(msg) if msg.starts_with(prefix))
+        }
+
+        let pk = "c5f93479093e2b8f724a79844cc10928dd44e9a390b539843fb83fbf842723f3";
+        let data = <[u8; 32]>::from_hex(pk).unwrap();
+        let mut reader = StrictReader::in_memory::<64>(data);
+        let internal_pk = InternalPk::strict_decode(&mut reader).unwrap();
+        assert_eq!(internal_pk, InternalPk::from_str(pk).unwrap());
+        for len in [0, 1, 31] {
+            let mut reader = StrictReader::in_memory::<64>(data[..len].to_vec());
+            let err = InternalPk::strict_decode(&mut reader).unwrap_err();
+            assert!(is_truncated(&err), "{len} bytes: {err:?}");
+        }
+        // complete input which is not a valid curve point
+        let mut reader = StrictReader::in_memory::<64>([0xFF; 32]);
+        let err = InternalPk::strict_decode(&mut reader).unwrap_err();
+        assert!(is_invalid(&err, "invalid x-only public key value"), "{err:?}");
+
+        let mut reader = StrictReader::in_memory::<64>(Vec::<u8>::new());
+        let err = LeafVer::strict_decode(&mut reader).unwrap_err();
+        assert!(is_truncated(&err), "{err:?}");
+        let mut reader = StrictReader::in_memory::<64>([0x51]);
+        let err = LeafVer::strict_decode(&mut reader).unwrap_err();
+        assert!(is_invalid(&err, "invalid taproot leaf version"), "{err:?}");
+    }
+
+    #[test]
+    #[cfg(feature = "serde")]
+    fn future_leaf_ver_serde_boundaries() {
+        use serde_crate::de::value::{Error, U8Deserializer};
+        use serde_crate::Deserialize;
+
+        for byte in LEAF_VER_BOUNDARIES {
+            let expected = match LeafVer::from_consensus_u8(byte) {
+                Ok(LeafVer::Future(version)) => Some(version),
+                _ => None,
+            };
+            let deserializer = U8Deserializer::<Error>::new(byte);
+            assert_eq!(
+                FutureLeafVer::deserialize(deserializer).ok(),
+                expected,
+                "serde decoding of leaf version {byte:#04x}"
+            );
+        }
+    }
+
+    #[test]
+    fn tap_script_builder() {
+        let prefix = TapScript::new().with_op(TapCode::Reserved).with_op(TapCode::Reserved);
+        let commitment = TapScript::new()
+            .with_op(TapCode::Return)
+            .with_slice(&[0xA5; 32]);
+        let script = prefix.clone().with_script(&commitment).unwrap();
+
+        let mut expected = vec![OP_RESERVED, OP_RESERVED, OP_RETURN, OP_PUSHBYTES_32];
+        expected.extend([0xA5; 32]);
+        assert_eq!(script.as_slice(), &expected[..]);
+
+        let mut extended = prefix;
+        extended.extend_from(&commitment).unwrap();
+        assert_eq!(extended, script);
+    }
+
+    #[test]
+    fn default_digest_backend() {
+        // Test vectors 0 and 1 from BIP-341 `scriptPubKey` wallet test vectors
+        let leaf = LeafScript::from_tap_script(TapScript::from_unsafe(
+            Vec::<u8>::from_hex(
+                "20d85a959b0290bf19bb89ed43c916be835475d013da4b362117393e25a48229b8ac",
+            )
+            .unwrap(),
+        ));
+        let leaf_hash = TapLeafHash::from_str(
+            "5b75adecf53548f3ec6ad7d78383bf84cc57b55a3127c72b9a2481752dd88b21",
+        )
+        .unwrap();
+        assert_eq!(TapLeafHash::with_raw_script(leaf.version, &leaf.script), leaf_hash);
+        assert_eq!(
+            TapLeafHash::with_raw_script_using(
+                &TapLeafHash::hasher::<Sha256>(),
+                leaf.version,
+                &leaf.script
+            ),
+            leaf_hash
+        );
+
+        for (internal_pk, root, tweak, output_pk) in [
+            (
+                "d6889cb081036e0faefa3a35157ad71086b123b2b144b649798b494c300a961d",
+                None,
+                "b86e7be8f39bab32a6f2c0443abbc210f0edac0e2c53d501b36b64437d9c6c70",
+                "53a1f6e454df1aa2776a2814a721372d6258050de330b3c6d10ee8f4e0dda343",
+            ),
+            (
+                "187791b6f712a8ea41c8ecdd0ee77fab3e85263b37e1ec18a3651926b3a6cf27",
+                Some(TapNodeHash::from(leaf_hash)),
+                "cbd8679ba636c1110ea247542cfbd964131a6be84f873f7f3b62a777528ed001",
+                "147c9c57132f6e7ecddba9800bb0c4449251c92a1e60371ee77557b6620f3ea3",
+            ),
+        ] {
+            let internal_pk = InternalPk::from_str(internal_pk).unwrap();
+            let output_pk = OutputPk::from_str(output_pk).unwrap();
+            assert_eq!(
+                internal_pk.tap_tweak(root).to_be_bytes(),
+                <[u8; 32]>::from_hex(tweak).unwrap()
+            );
+            assert_eq!(internal_pk.to_output_pk_with_root(root), (output_pk, Parity::Odd));
+            assert_eq!(
+                internal_pk.to_output_pk_using(&InternalPk::tap_tweak_hasher::<Sha256>(), root),
+                (output_pk, Parity::Odd)
+            );
+        }
+
+        let node1 = TapNodeHash::from([0xA1; 32]);
+        let node2 = TapNodeHash::from([0xB2; 32]);
+        let mut engine = Sha256::from_tag(MIDSTATE_TAPBRANCH);
+        engine.input_raw(node1.as_slice());
+        engine.input_raw(node2.as_slice());
+        let branch = TapBranchHash::from(engine.finish());
+        assert_eq!(TapBranchHash::with_nodes(node2, node1), branch);
+        assert_eq!(
+            TapBranchHash::with_nodes_using(&TapBranchHash::hasher::<Sha256>(), node2, node1),
+            branch
+        );
+    }
+
+    #[test]
+    fn tap_tweak() {
+        let internal_pk = internal_pk();
+        for root in [None, Some(TapNodeHash::from([0xA1; 32]))] {
+            let (output_pk, parity) = internal_pk.to_output_pk_with_root(root);
+            let (tweaked, tweaked_parity) = internal_pk
+                .add_tweak(secp256k1::SECP256K1, &internal_pk.tap_tweak(root))
+                .unwrap();
+            assert_eq!(tweaked, **output_pk);
+            assert_eq!(Parity::from_secp(tweaked_parity), parity);
+        }
+        assert_ne!(
+            internal_pk.tap_tweak(None).to_be_bytes(),
+            internal_pk.tap_tweak(Some(TapNodeHash::from([0xA1; 32]))).to_be_bytes()
+        );
+
+        let mut engine = Sha256::from_tag(MIDSTATE_TAPTWEAK);
+        engine.input_raw(&internal_pk.to_byte_array());
+        engine.input_raw(&[0xA1; 32]);
+        assert_eq!(
+            internal_pk
+                .tap_tweak(Some(TapNodeHash::from([0xA1; 32])))
+                .to_be_bytes(),
+            engine.finish()
+        );
+    }
+
+    #[test]
+    fn tap_leaf_hash_inline() {
+        // leaf hashes with multi-byte script length prefix
+        for len in [0, 1, 0xFC, 0xFD, 0x100] {
+            let leaf = LeafScript::from_tap_script(TapScript::from_unsafe(vec![OP_PUSHNUM_1; len]));
+            let mut engine = Sha256::from_tag(MIDSTATE_TAPLEAF);
+            engine.input_raw(&[leaf.version.to_consensus_u8()]);
+            engine.input_raw(&leaf.script.len_var_int().consensus_serialize());
+            engine.input_raw(leaf.script.as_slice());
+            assert_eq!(
+                leaf.tap_leaf_hash(),
+                TapLeafHash::from(engine.finish()),
+                "script of {len} bytes"
+            );
+        }
+    }
+
+    #[test]
+    fn tap_leaf_hash_paths() {
+        for tap_script in [
+            TapScript::new(),
+            TapScript::from_unsafe(vec![OP_PUSHNUM_1]),
+            TapScript::from_unsafe(vec![OP_RETURN; 0x100]),
+        ] {
+            let leaf_script = LeafScript::from_tap_script(tap_script.clone());
+            let hash = TapLeafHash::with_tap_script(&tap_script);
+            assert_eq!(hash, TapLeafHash::with_leaf_script(&leaf_script));
+            assert_eq!(
+                hash,
+                TapLeafHash::with_raw_script(LeafVer::TapScript, tap_script.as_script_bytes())
+            );
+        }
+    }
+
+    #[test]
+    fn tap_script_minimal_pushes() {
+        let mut script = TapScript::new();
+        script.push_slice(&[0xA5; 5]);
+        script.push_slice(&[0xA5; 80]);
+        script.push_slice(&[0xA5; 300]);
+        script.push_opcode(TapCode::Return);
+        assert!(script.is_minimally_encoded());
+        assert!(TapScript::from_unsafe(vec![OP_PUSHBYTES_0, OP_PUSHNUM_1]).is_minimally_encoded());
+
+        // short values pushed with OP_PUSHDATA1
+        let mut bytes = vec![OP_PUSHDATA1, 5];
+        bytes.extend([0xA5; 5]);
+        assert!(!TapScript::from_unsafe(bytes).is_minimally_encoded());
+        // small numbers must use OP_PUSHNUM opcodes
+        assert!(!TapScript::from_unsafe(vec![OP_PUSHBYTES_1, 0x05]).is_minimally_encoded());
+        assert!(!TapScript::from_unsafe(vec![OP_PUSHBYTES_1, 0x81]).is_minimally_encoded());
+        // 80 bytes pushed with OP_PUSHDATA2
+        let mut bytes = vec![OP_PUSHDATA2, 80, 0];
+        bytes.extend([0xA5; 80]);
+        assert!(!TapScript::from_unsafe(bytes).is_minimally_encoded());
+        // push running past the end of the script
+        assert!(!TapScript::from_unsafe(vec![OP_PUSHBYTES_32, 0xA5]).is_minimally_encoded());
+        assert!(!TapScript::from_unsafe(vec![OP_PUSHDATA2, 0x01]).is_minimally_encoded());
+    }
+
+    #[test]
+    fn parity_secp() {
+        for parity in [Parity::Even, Parity::Odd] {
+            assert_eq!(Parity::from_secp(parity.to_secp()), parity);
+            assert_eq!(parity.to_secp().to_u8(), parity.to_consensus_u8());
+        }
+        for parity in [secp256k1::Parity::Even, secp256k1::Parity::Odd] {
+            assert_eq!(Parity::from_secp(parity).to_secp(), parity);
+            assert_eq!(secp256k1::Parity::from(Parity::from(parity)), parity);
+        }
+    }
+
+    #[test]
+    fn parity_from_str() {
+        for parity in [Parity::Even, Parity::Odd] {
+            assert_eq!(Parity::from_str(&parity.to_string()), Ok(parity));
+            assert_eq!(Parity::from_str(&parity.to_consensus_u8().to_string()), Ok(parity));
+        }
+        assert_eq!(Parity::from_str("EVEN"), Ok(Parity::Even));
+        assert_eq!(Parity::from_str("Odd"), Ok(Parity::Odd));
+        assert_eq!(Parity::from_str("2"), Err(InvalidParityValue(2).into()));
+        assert_eq!(
+            Parity::from_str("neither"),
+            Err(ParityParseError::InvalidString(s!("neither")))
+        );
+    }
+
+    #[test]
+    fn balanced_tree() {
+        assert_eq!(TapTree::balanced(scripts(0)), Err(TapTreeError::NoLeaves));
+
+        let leaves = scripts(4)
+            .iter()
+            .map(LeafScript::tap_leaf_hash)
+            .map(TapNodeHash::from)
+            .collect::<Vec<_>>();
+        let branch = |a: TapNodeHash, b: TapNodeHash| -> TapNodeHash {
+            TapBranchHash::with_nodes(a, b).into()
+        };
+
+        let tree = TapTree::balanced(scripts(1)).unwrap();
+        assert_eq!(depths(&tree), vec![0]);
+        assert_eq!(tree.merkle_root(), leaves[0]);
+        assert_eq!(
+            tree.merkle_root().to_string(),
+            "a85b2107f791b26a84e7586c28cec7cb61202ed3d01944d832500f363782d675"
+        );
+
+        let tree = TapTree::balanced(scripts(2)).unwrap();
+        assert_eq!(depths(&tree), vec![1, 1]);
+        assert_eq!(tree.merkle_root(), branch(leaves[0], leaves[1]));
+        assert_eq!(
+            tree.merkle_root().to_string(),
+            "6496f0779f38b871013be71ee7dcce8fcdcc02afc4c688acb159fc5de2fba55e"
+        );
+
+        let tree = TapTree::balanced(scripts(3)).unwrap();
+        assert_eq!(depths(&tree), vec![2, 2, 1]);
+        assert_eq!(tree.merkle_root(), branch(branch(leaves[0], leaves[1]), leaves[2]));
+        assert_eq!(
+            tree.merkle_root().to_string(),
+            "609f09890e4348cc5bcc26e51c432c7830162715d64aab7c306f853c9da06a7b"
+        );
+
+        let tree = TapTree::balanced(scripts(4)).unwrap();
+        assert_eq!(depths(&tree), vec![2, 2, 2, 2]);
+        assert_eq!(
+            tree.merkle_root(),
+            branch(branch(leaves[0], leaves[1]), branch(leaves[2], leaves[3]))
+        );
+        assert_eq!(
+            tree.merkle_root().to_string(),
+            "090341374d3b6413412cacb8bf8c73cead86cbc093dfb2c51a0743f72c3cc693"
+        );
+    }
+
+    #[test]
+    fn incomplete_tree() {
+        let leaves = |depths: &[u8]| {
+            depths
+                .iter()
+                .zip(scripts(depths.len() as u8))
+                .map(|(depth, script)| LeafInfo::with(*depth, script))
+                .collect::<Vec<_>>()
+        };
+        assert_eq!(TapTree::with_leaves(leaves(&[1])), Err(TapTreeError::IncompleteTree));
+        assert_eq!(TapTree::with_leaves(leaves(&[0, 0])), Err(TapTreeError::IncompleteTree));
+        assert_eq!(TapTree::with_leaves(leaves(&[2, 1, 2])), Err(TapTreeError::IncompleteTree));
+        assert_eq!(TapTree::with_leaves(leaves(&[1, 1, 1])), Err(TapTreeError::IncompleteTree));
+        assert!(TapTree::with_leaves(leaves(&[1, 2, 2])).is_ok());
+    }
+
+    #[test]
+    fn max_depth_tree() {
+        let leaf = |no: u8| LeafScript::from_tap_script(TapScript::from_unsafe(vec![no]));
+        let mut leaves = (0..TAPROOT_MAX_DEPTH)
+            .map(|no| LeafInfo::with(no + 1, leaf(no)))
+            .collect::<Vec<_>>();
+        leaves.push(LeafInfo::with(TAPROOT_MAX_DEPTH, leaf(TAPROOT_MAX_DEPTH)));
+
+        let tree = TapTree::with_leaves(leaves.clone()).unwrap();
+        assert_eq!(
+            tree.merkle_root().to_string(),
+            "125165958a66cdcf3c9985db7cbc2367814940bbda35300e69334145377f1cef"
+        );
+
+        leaves.pop();
+        leaves.push(LeafInfo::with(TAPROOT_MAX_DEPTH + 1, leaf(TAPROOT_MAX_DEPTH)));
+        leaves.push(LeafInfo::with(TAPROOT_MAX_DEPTH + 1, leaf(TAPROOT_MAX_DEPTH + 1)));
+        assert_eq!(TapTree::with_leaves(leaves), Err(TapTreeError::MaxDepthExceeded));
+    }
+
+    #[test]
+    fn tree_split() {
+        assert_eq!(TapTree::balanced(scripts(1)).unwrap().split(), None);
+
+        let tree = TapTree::balanced(scripts(3)).unwrap();
+        let (left, right) = tree.split().unwrap();
+        assert_eq!(depths(&left), vec![1, 1]);
+        assert_eq!(depths(&right), vec![0]);
+        assert_eq!(
+            TapNodeHash::from(TapBranchHash::with_nodes(left.merkle_root(), right.merkle_root())),
+            tree.merkle_root()
+        );
+    }
+
+    #[test]
+    fn control_block_witness() {
+        let internal_pk = internal_pk();
+        let leaves = scripts(3);
+        let tree = TapTree::balanced(leaves.clone()).unwrap();
+        let sig = vec![0x01; 64];
+        let annex = vec![TAPROOT_ANNEX_PREFIX, 0x01, 0x02];
+
+        for leaf in &leaves {
+            let plan = plan_script_spend(internal_pk, &tree, leaf).unwrap();
+            let control_block = plan.control_block;
+
+            let witness = control_block.clone().into_witness(leaf, vec![sig.clone()]);
+            let elements = witness.elements().collect::<Vec<_>>();
+            assert_eq!(elements.len(), 3);
+            assert_eq!(elements[0], &sig[..]);
+            assert_eq!(elements[1], leaf.script.as_slice());
+            assert_eq!(
+                elements[2][0],
+                control_block_first_byte(LeafVer::TapScript, plan.output_parity)
+            );
+            assert_eq!(elements[2].len(), 33 + 32 * control_block.merkle_branch.len());
+            let decoded = ControlBlock::consensus_deserialize(elements[2]).unwrap();
+            assert_eq!(decoded, control_block);
+            let script = LeafScript::with_bytes(decoded.leaf_version, elements[1].to_vec())
+                .unwrap();
+            assert!(decoded.verify(plan.output_pk, &script));
+
+            let witness = control_block
+                .clone()
+                .into_witness_with_annex(leaf, vec![sig.clone()], annex.clone())
+                .unwrap();
+            let elements = witness.elements().collect::<Vec<_>>();
+            assert_eq!(elements.len(), 4);
+            assert_eq!(elements[1], leaf.script.as_slice());
+            assert_eq!(elements[3], &annex[..]);
+            let decoded = ControlBlock::consensus_deserialize(elements[2]).unwrap();
+            assert_eq!(decoded, control_block);
+            let script = LeafScript::with_bytes(decoded.leaf_version, elements[1].to_vec())
+                .unwrap();
+            assert!(decoded.verify(plan.output_pk, &script));
+        }
+    }
+
+    #[test]
+    fn tap_tree_spending_keys() {
+        let pk1 = XOnlyPk::from_str(
+            "f9308a019258c31049344f85f89d5229b531c845836f99b08601f113bce036f9",
+        )
+        .unwrap();
+        let pk2 = XOnlyPk::from_str(
+            "dff1d77f2a671c5f36183726db2341be58feae1da2deced843240f7b502ba659",
+        )
+        .unwrap();
+        let script = |prefix: &[u8], pk: XOnlyPk, suffix: &[u8]| {
+            let mut script = prefix.to_vec();
+            script.push(OP_PUSHBYTES_32);
+            script.extend(pk.to_byte_array());
+            script.extend(suffix);
+            LeafScript::from_tap_script(TapScript::from_unsafe(script))
+        };
+        let leaf1 = script(&[], pk1, &[OP_CHECKSIG]);
+        let leaf2 = script(&[], pk2, &[OP_CHECKSIGVERIFY, OP_PUSHNUM_1]);
+        let tree = TapTree::balanced([leaf1, leaf2]).unwrap();
+        assert_eq!(tree.spending_keys(), bset![pk1, pk2]);
+
+        // 32-byte pushes not followed by signature checks are not keys
+        let hash_lock = script(&[OP_SHA256], pk1, &[OP_EQUAL]);
+        let tree = TapTree::balanced([hash_lock]).unwrap();
+        assert!(tree.spending_keys().is_empty());
+    }
+
+    #[test]
+    fn tap_merkle_path_from_bytes() {
+        let hashes = [[0xA1; 32], [0xB2; 32], [0xC3; 32]];
+        let bytes = hashes.concat();
+        let path = TapMerklePath::try_from_bytes(&bytes).unwrap();
+        assert_eq!(path, TapMerklePath::try_from_iter(hashes.map(TapBranchHash::from)).unwrap());
+        assert_eq!(TapMerklePath::try_from_bytes(&[]).unwrap(), none!());
+
+        assert_eq!(
+            TapMerklePath::try_from_bytes(&[0xA1; 31]),
+            Err(TapMerklePathError::InvalidLength(31))
+        );
+        assert_eq!(
+            TapMerklePath::try_from_bytes(&bytes[..95]),
+            Err(TapMerklePathError::InvalidLength(95))
+        );
+        assert_eq!(
+            TapMerklePath::try_from_bytes(&[0xA1; 129 * 32]),
+            Err(TapMerklePathError::TooLong(129))
+        );
+    }
+
+    #[test]
+    fn tap_merkle_path_intermediate_nodes() {
+        let leaves = scripts(3);
+        let tree = TapTree::balanced(leaves.clone()).unwrap();
+        for leaf in &leaves {
+            let path = tree.merkle_path(leaf).unwrap();
+            let nodes = path.intermediate_nodes(leaf.tap_leaf_hash());
+            assert_eq!(nodes.len(), path.len() + 1);
+            assert_eq!(nodes[0], leaf.tap_leaf_hash().into_tap_hash());
+            assert_eq!(nodes.last(), Some(&tree.merkle_root()));
+        }
+
+        let leaf = leaves[0].tap_leaf_hash();
+        let nodes = TapMerklePath::default().intermediate_nodes(leaf);
+        assert_eq!(nodes, vec![leaf.into_tap_hash()]);
+    }
+
+    #[test]
+    fn tap_merkle_path_shape() {
+        let leaves = scripts(5);
+        let tree = TapTree::balanced(leaves.clone()).unwrap();
+        for leaf in &leaves {
+            let path = tree.merkle_path(leaf).unwrap();
+            assert_eq!(path.validate_shape(leaf.tap_leaf_hash()), Ok(()));
+        }
+        let leaf = leaves[0].tap_leaf_hash();
+        assert_eq!(TapMerklePath::default().validate_shape(leaf), Ok(()));
+
+        let siblings = [[0xA1; 32], [0xB2; 32], [0xB2; 32]].map(TapBranchHash::from);
+        let path = TapMerklePath::try_from_iter(siblings).unwrap();
+        assert_eq!(path.validate_shape(leaf), Err(TapMerklePathShapeError::DuplicateSiblings(2)));
+
+        let path = TapMerklePath::try_from(vec![TapBranchHash::from(leaf.into_inner())]).unwrap();
+        assert_eq!(path.validate_shape(leaf), Err(TapMerklePathShapeError::SiblingMatchesNode(0)));
+
+        let node = TapBranchHash::with_nodes(leaf.into_tap_hash(), [0xA1; 32].into());
+        let path = TapMerklePath::try_from(vec![
+            TapBranchHash::from([0xA1; 32]),
+            node,
+        ])
+        .unwrap();
+        assert_eq!(path.validate_shape(leaf), Err(TapMerklePathShapeError::SiblingMatchesNode(1)));
+    }
+
+    #[test]
+    fn hash_types_constructors() {
+        assert_constructors_consistent::<TapLeafHash>();
+        assert_constructors_consistent::<TapBranchHash>();
+        assert_constructors_consistent::<TapNodeHash>();
+    }
+
+    #[test]
+    #[cfg(feature = "serde")]
+    fn serde_formats() {
+        let tap_script = TapScript::from_unsafe(vec![OP_PUSHNUM_1, OP_PUSHNUM_1 + 1]);
+        let json = serde_json::to_string(&tap_script).unwrap();
+        assert_eq!(json, "\"5152\"");
+        assert_eq!(serde_json::from_str::<TapScript>(&json).unwrap(), tap_script);
+        let bin = bincode::serialize(&tap_script).unwrap();
+        assert_eq!(bin, [2, 0, 0, 0, 0, 0, 0, 0, 0x51, 0x52]);
+        assert_eq!(bincode::deserialize::<TapScript>(&bin).unwrap(), tap_script);
+
+        let leaf_script = LeafScript::from_tap_script(tap_script);
+        let json = serde_json::to_string(&leaf_script).unwrap();
+        assert!(json.contains("\"script\":\"5152\""));
+        assert_eq!(serde_json::from_str::<LeafScript>(&json).unwrap(), leaf_script);
+        let bin = bincode::serialize(&leaf_script).unwrap();
+        assert_eq!(bincode::deserialize::<LeafScript>(&bin).unwrap(), leaf_script);
+
+        let control_block = ControlBlock::with(
+            LeafVer::TapScript,
+            internal_pk(),
+            Parity::Odd,
+            TapMerklePath::try_from(vec![TapBranchHash::from([0xA5; 32])]).unwrap(),
+        );
+        let json = serde_json::to_string(&control_block).unwrap();
+        assert_eq!(serde_json::from_str::<ControlBlock>(&json).unwrap(), control_block);
+        let bin = bincode::serialize(&control_block).unwrap();
+        assert_eq!(bincode::deserialize::<ControlBlock>(&bin).unwrap(), control_block);
+    }
+
+    #[test]
+    fn control_block_summary() {
+        let internal_pk = internal_pk();
+        let siblings = [[0xA1; 32], [0xB2; 32], [0xC3; 32]].map(TapBranchHash::from);
+        let path = TapMerklePath::try_from_iter(siblings).unwrap();
+        let control_block = ControlBlock::with(LeafVer::TapScript, internal_pk, Parity::Odd, path);
+        assert_eq!(control_block.summary(), "ctrl[v=c0 parity=odd ik=c5f93479.. depth=3]");
+
+        let future = LeafVer::from_consensus_u8(0xc2).unwrap();
+        let control_block = ControlBlock::with(future, internal_pk, Parity::Even, none!());
+        assert_eq!(control_block.summary(), "ctrl[v=c2 parity=even ik=c5f93479.. depth=0]");
+    }
+
+    #[test]
+    fn taproot_context_shared() {
+        use std::sync::Arc;
+        use std::thread;
+
+        let internal_pk = internal_pk();
+        let leaves = scripts(2);
+        let sibling = TapBranchHash::from(leaves[1].tap_leaf_hash().into_inner());
+        let merkle_root = TapBranchHash::with_nodes(
+            leaves[0].tap_leaf_hash().into_tap_hash(),
+            leaves[1].tap_leaf_hash().into_tap_hash(),
+        )
+        .into_tap_hash();
+        let (output_pk, parity) = internal_pk.to_output_pk_with_root(Some(merkle_root));
+        let path = TapMerklePath::try_from_iter([sibling]).unwrap();
+        let control_block = ControlBlock::with(LeafVer::TapScript, internal_pk, parity, path);
+
+        let ctx = Arc::new(TaprootContext::randomized(&[0xA5; 32]));
+        let handles = (0..4)
+            .map(|_| {
+                let ctx = Arc::clone(&ctx);
+                let control_block = control_block.clone();
+                let leaves = leaves.clone();
+                thread::spawn(move || {
+                    assert_eq!(ctx.to_output_pk(internal_pk, Some(merkle_root)), (
+                        output_pk, parity
+                    ));
+                    assert_eq!(
+                        ctx.to_output_pk(internal_pk, None),
+                        internal_pk.to_output_pk_with_root(None)
+                    );
+                    assert!(ctx.verify_control_block(&control_block, output_pk, &leaves[0]));
+                    assert!(!ctx.verify_control_block(&control_block, output_pk, &leaves[1]));
+                })
+            })
+            .collect::<Vec<_>>();
+        for handle in handles {
+            handle.join().unwrap();
+        }
+        assert!(control_block.verify(output_pk, &leaves[0]));
+        assert_eq!(TaprootContext::default().to_output_pk(internal_pk, Some(merkle_root)), (
+            output_pk, parity
+        ));
+    }
+
+    #[test]
+    fn provably_unspendable() {
+        let mut script = vec![OP_RETURN, OP_PUSHBYTES_32];
+        script.extend([0x50; 32]);
+        assert!(TapScript::from_unsafe(script.clone()).is_provably_unspendable());
+        assert!(TapScript::from_unsafe(vec![OP_RETURN]).is_provably_unspendable());
+        // truncated push fails decoding
+        assert!(TapScript::from_unsafe(script[..20].to_vec()).is_provably_unspendable());
+
+        assert!(!TapScript::from_unsafe(vec![OP_PUSHNUM_1]).is_provably_unspendable());
+        assert!(!TapScript::from_unsafe(vec![]).is_provably_unspendable());
+        assert!(!TapScript::from_unsafe(vec![OP_PUSHNUM_1, OP_RETURN]).is_provably_unspendable());
+        // OP_SUCCESS80 overrides OP_RETURN
+        assert!(!TapScript::from_unsafe(vec![OP_RETURN, 0x50]).is_provably_unspendable());
+
+        // tapret commitment leaf
+        let mut tapret = vec![0x50; 29];
+        tapret.extend([OP_RETURN, OP_PUSHBYTES_33]);
+        tapret.extend([0xA5; 33]);
+        assert!(!TapScript::from_unsafe(tapret).is_provably_unspendable());
+    }
+
+    #[test]
+    fn is_p2tr_short_scripts() {
+        let mut p2tr = vec![OP_PUSHNUM_1, OP_PUSHBYTES_32];
+        p2tr.extend([0xA5; 33]);
+        for len in [0, 1, 2, 33, 34, 35] {
+            let script = ScriptPubkey::from_unsafe(p2tr[..len].to_vec());
+            assert_eq!(script.is_p2tr(), len == 34, "script of {len} bytes");
+        }
+    }
+
+    #[test]
+    fn control_byte_roundtrip() {
+        for version in [0xC0, 0xC2, 0x52, 0x00, 0xFE] {
+            let version = LeafVer::from_consensus_u8(version).unwrap();
+            for parity in [Parity::Even, Parity::Odd] {
+                let byte = control_block_first_byte(version, parity);
+                assert_eq!(byte & TAPROOT_LEAF_MASK, version.to_consensus_u8());
+                assert_eq!(split_control_byte(byte), Ok((version, parity)));
+            }
+        }
+        assert_eq!(control_block_first_byte(LeafVer::TapScript, Parity::Odd), 0xC1);
+        assert_eq!(split_control_byte(0x50), Err(InvalidLeafVer(0x50)));
+        assert_eq!(split_control_byte(0x51), Err(InvalidLeafVer(0x50)));
+    }
+
+    #[test]
+    fn single_leaf_control_block() {
+        let internal_pk = internal_pk();
+        let leaf = scripts(1).remove(0);
+        let tree = TapTree::balanced([leaf.clone()]).unwrap();
+        let leaf_hash = leaf.tap_leaf_hash();
+        assert_eq!(tree.merkle_root(), leaf_hash.into_tap_hash());
+
+        let plan = plan_script_spend(internal_pk, &tree, &leaf).unwrap();
+        let control_block = plan.control_block;
+        assert!(control_block.merkle_branch.is_empty());
+        assert_eq!(control_block.consensus_serialize().len(), 33);
+
+        let (output_pk, parity) = internal_pk.to_output_pk(Some(leaf_hash));
+        assert_eq!((plan.output_pk, plan.output_parity), (output_pk, parity));
+        assert_eq!(control_block.derived_output_pk(&leaf), (output_pk, parity));
+        assert!(control_block.verify(output_pk, &leaf));
+
+        let other_leaf = scripts(2).remove(1);
+        assert!(!control_block.verify(output_pk, &other_leaf));
+        let (key_only_pk, _) = internal_pk.to_output_pk_with_root(None);
+        assert!(!control_block.verify(key_only_pk, &leaf));
+    }
+
+    #[test]
+    fn classify_spend() {
+        let internal_pk = internal_pk();
+        let leaves = scripts(2);
+        let control_block = ControlBlock::with(
+            LeafVer::TapScript,
+            internal_pk,
+            Parity::Odd,
+            TapMerklePath::try_from(vec![TapBranchHash::from([0xA5; 32])]).unwrap(),
+        );
+        let sig = vec![0x01; 64];
+        let annex = vec![TAPROOT_ANNEX_PREFIX, 0x01, 0x02];
+
+        let witness = Witness::from_consensus_stack([sig.clone()]);
+        assert_eq!(classify_taproot_spend(&witness), TaprootSpendKind::KeyPath);
+        let witness = Witness::from_consensus_stack([vec![0x01; 65], annex.clone()]);
+        assert_eq!(classify_taproot_spend(&witness), TaprootSpendKind::KeyPath);
+
+        let expected = TaprootSpendKind::ScriptPath {
+            control_block: control_block.clone(),
+            leaf_script: leaves[0].clone(),
+        };
+        let witness = control_block
+            .clone()
+            .into_witness(&leaves[0], vec![sig.clone()]);
+        assert_eq!(classify_taproot_spend(&witness), expected);
+        let witness = control_block
+            .clone()
+            .into_witness_with_annex(&leaves[0], vec![sig.clone()], annex.clone())
+            .unwrap();
+        assert_eq!(classify_taproot_spend(&witness), expected);
+
+        let witness = Witness::default();
+        assert_eq!(classify_taproot_spend(&witness), TaprootSpendKind::Malformed);
+        let witness = Witness::from_consensus_stack([vec![0x01; 63]]);
+        assert_eq!(classify_taproot_spend(&witness), TaprootSpendKind::Malformed);
+        let witness = Witness::from_consensus_stack([annex.clone()]);
+        assert_eq!(classify_taproot_spend(&witness), TaprootSpendKind::Malformed);
+        let witness = Witness::from_consensus_stack([sig, vec![0x51], vec![0xC0; 34]]);
+        assert_eq!(classify_taproot_spend(&witness), TaprootSpendKind::Malformed);
+    }
+
+    #[test]
+    fn control_block_invalid_annex() {
+        let internal_pk = internal_pk();
+        let leaves = scripts(1);
+        let control_block =
+            ControlBlock::with(LeafVer::TapScript, internal_pk, Parity::Even, none!());
+        assert_eq!(
+            control_block
+                .clone()
+                .into_witness_with_annex(&leaves[0], vec![], vec![0x01]),
+            Err(InvalidAnnex(Some(0x01)))
+        );
+        assert_eq!(
+            control_block.into_witness_with_annex(&leaves[0], vec![], vec![]),
+            Err(InvalidAnnex(None))
+        );
+    }
+
+    #[test]
+    fn control_block_bip341() {
+        // Test vector from BIP-341 `scriptPubKey` wallet test vectors
+        let control_block = ControlBlock::consensus_deserialize(
+            Vec::<u8>::from_hex(
+                "c1187791b6f712a8ea41c8ecdd0ee77fab3e85263b37e1ec18a3651926b3a6cf27",
+            )
+            .unwrap(),
+        )
+        .unwrap();
+        let leaf = LeafScript::from_tap_script(TapScript::from_unsafe(
+            Vec::<u8>::from_hex(
+                "20d85a959b0290bf19bb89ed43c916be835475d013da4b362117393e25a48229b8ac",
+            )
+            .unwrap(),
+        ));
+        let output_pk =
+            OutputPk::from_str("147c9c57132f6e7ecddba9800bb0c4449251c92a1e60371ee77557b6620f3ea3")
+                .unwrap();
+
+        assert_eq!(
+            control_block.internal_pk(),
+            InternalPk::from_str(
+                "187791b6f712a8ea41c8ecdd0ee77fab3e85263b37e1ec18a3651926b3a6cf27"
+            )
+            .unwrap()
+        );
+        assert_eq!(control_block.derived_output_pk(&leaf), (output_pk, Parity::Odd));
+        assert!(control_block.verify(output_pk, &leaf));
+    }
+
+    #[test]
+    fn script_spend_plan() {
+        let internal_pk = internal_pk();
+        let leaves = scripts(3);
+        let tree = TapTree::balanced(leaves.clone()).unwrap();
+
+        for (leaf, path_len) in leaves.iter().zip([2, 2, 1]) {
+            let plan = plan_script_spend(internal_pk, &tree, leaf).unwrap();
+            assert_eq!(plan.control_block.merkle_branch.len(), path_len);
+            assert_eq!(plan.merkle_root, tree.merkle_root());
+            assert_eq!(plan.leaf_hash, leaf.tap_leaf_hash());
+            assert_eq!(plan.control_block.output_key_parity, plan.output_parity);
+            assert_eq!(
+                plan.output_pk.to_script_pubkey(),
+                ScriptPubkey::p2tr(internal_pk, Some(tree.merkle_root()))
+            );
+            assert!(plan.control_block.verify(plan.output_pk, leaf));
+            assert_eq!(plan.control_block.internal_pk(), internal_pk);
+            assert_eq!(
+                plan.control_block.derived_output_pk(leaf),
+                (plan.output_pk, plan.output_parity)
+            );
+            assert!(!plan.control_block.verify(plan.output_pk, &scripts(4)[3]));
+        }
+
+        assert_eq!(plan_script_spend(internal_pk, &tree, &scripts(4)[3]), None);
+    }
 }
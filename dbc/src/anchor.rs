@@ -26,9 +26,11 @@
 
 use std::error::Error;
 
+use amplify::confinement::U32;
 use bc::Tx;
 use commit_verify::mpc::{self, Message, ProtocolId};
-use strict_encoding::{StrictDumb, StrictEncode};
+use commit_verify::{DigestExt, Sha256};
+use strict_encoding::{StreamWriter, StrictDumb, StrictEncode};
 
 use crate::{DbcMethod, Method, LIB_NAME_BPCORE};
 
@@ -83,8 +85,8 @@ impl<L: mpc::Proof + StrictDumb, D: dbc::Proof<M>, M: DbcMethod> Anchor<L, D, M>
     pub fn new(mpc_proof: L, dbc_proof: D) -> Self {
         Self {
             mpc_proof,
+            method: dbc_proof.method(),
             dbc_proof,
-            method: D::METHOD,
         }
     }
 
@@ -100,6 +102,28 @@ impl<L: mpc::Proof + StrictDumb, D: dbc::Proof<M>, M: DbcMethod> Anchor<L, D, M>
             self.dbc_proof == other.dbc_proof &&
             self.method == other.method
     }
+
+    /// Tag used for computing [`Self::commitment_id`]. Refers to LNPBP-4,
+    /// which defines anchors, and to the version of the identifier.
+    pub const COMMITMENT_ID_TAG: &'static str = "urn:lnp-bp:lnpbp4:anchor-id#v1";
+
+    /// Computes anchor identifier as a tagged hash of the anchor strict
+    /// encoding, covering the method, DBC and MPC proofs.
+    ///
+    /// The identifier doesn't depend on the witness transaction and allows to
+    /// deduplicate and canonically order anchors, including anchors of
+    /// different methods committing to the same message. Since anchors don't
+    /// contain the number of the transaction output holding the commitment,
+    /// it is not covered by the identifier. Anchors revealing different parts
+    /// of the same MPC tree have different identifiers; use [`Self::matches`]
+    /// to compare them.
+    pub fn commitment_id(&self) -> [u8; 32] {
+        let mut writer = StreamWriter::in_memory::<U32>();
+        self.strict_write(&mut writer).expect("anchor strict encoding must fit 4GB");
+        let mut engine = Sha256::from_tag(Self::COMMITMENT_ID_TAG);
+        engine.input_raw(&writer.unconfine());
+        engine.finish()
+    }
 }
 
 /// Error merging two [`Anchor`]s.
@@ -158,6 +182,25 @@ impl<D: dbc::Proof<M>, M: DbcMethod> Anchor<mpc::MerkleProof, D, M> {
         Ok(mpc_commitment)
     }
 
+    /// Verifies that the anchor commits to the given message under the given
+    /// protocol and that the resulting DBC commitment matches the 32-byte
+    /// value `observed` in the witness transaction output (OP_RETURN payload
+    /// for opret and output key for tapret).
+    ///
+    /// Unlike [`Self::verify`], does not require access to the witness
+    /// transaction.
+    pub fn verify_against(
+        &self,
+        protocol_id: impl Into<ProtocolId>,
+        message: impl Into<Message>,
+        observed: [u8; 32],
+    ) -> bool {
+        let Ok(mpc_commitment) = self.convolve(protocol_id, message) else {
+            return false;
+        };
+        self.dbc_proof.commitment_value(&mpc_commitment) == Some(observed)
+    }
+
     /// Verifies that the anchor commits to the given message under the given
     /// protocol.
     pub fn convolve(
@@ -213,3 +256,136 @@ impl<D: dbc::Proof<M>, M: DbcMethod> Anchor<mpc::MerkleBlock, D, M> {
         Ok(self)
     }
 }
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::opret::OpretProof;
+    use crate::tapret::{TapretPathProof, TapretProof};
+    use crate::test_helpers::internal_pk;
+    use crate::DbcProof;
+
+    fn tapret_anchor(nonce: u8) -> Anchor<mpc::MerkleProof, TapretProof> {
+        let internal_pk = internal_pk();
+        Anchor::new(mpc::MerkleProof::strict_dumb(), TapretProof {
+            path_proof: TapretPathProof::root(nonce),
+            internal_pk,
+        })
+    }
+
+    #[test]
+    fn commitment_id() {
+        assert_eq!(tapret_anchor(0).commitment_id(), tapret_anchor(0).commitment_id());
+        assert_ne!(tapret_anchor(0).commitment_id(), tapret_anchor(1).commitment_id());
+
+        let opret = Anchor::<_, OpretProof>::new(mpc::MerkleProof::strict_dumb(), default!());
+        let same = Anchor::<_, OpretProof>::new(mpc::MerkleProof::strict_dumb(), default!());
+        assert_eq!(opret.commitment_id(), same.commitment_id());
+        assert_ne!(opret.commitment_id(), tapret_anchor(0).commitment_id());
+    }
+
+    #[test]
+    fn mpc_root_insertion_order() {
+        use amplify::confinement::Confined;
+        use commit_verify::{CommitId, TryCommitVerify};
+
+        let entries = (1u8..=5)
+            .map(|no| (ProtocolId::from([no * 0x11; 32]), Message::from([no; 32])))
+            .collect::<Vec<_>>();
+        assert!(ProtocolId::from([0x11; 32]) < ProtocolId::from([0x22; 32]));
+
+        let tree = |entries: Vec<(ProtocolId, Message)>| {
+            let source = mpc::MultiSource {
+                method: mpc::Method::Sha256t,
+                min_depth: 3,
+                messages: Confined::try_from_iter(entries).unwrap(),
+                static_entropy: Some(0xdead_beef),
+            };
+            mpc::MerkleTree::try_commit(&source).unwrap()
+        };
+        let forward = tree(entries.clone());
+        let reverse = tree(entries.iter().rev().copied().collect());
+        let mut shuffled = entries.clone();
+        shuffled.swap(0, 3);
+        shuffled.swap(1, 4);
+        let shuffled = tree(shuffled);
+        assert_eq!(forward.commit_id(), reverse.commit_id());
+        assert_eq!(forward.commit_id(), shuffled.commit_id());
+
+        let block = mpc::MerkleBlock::from(reverse);
+        for (protocol_id, message) in entries {
+            let anchor = Anchor::new(
+                block.to_merkle_proof(protocol_id).unwrap(),
+                OpretProof::default(),
+            );
+            assert_eq!(anchor.convolve(protocol_id, message), Ok(forward.commit_id()));
+        }
+    }
+
+    #[test]
+    fn verify_against() {
+        use amplify::confinement::Confined;
+        use bc::opcodes::OP_RETURN;
+        use bc::{LockTime, ScriptPubkey, TxOut, TxVer, VarIntArray};
+        use commit_verify::{ConvolveCommit, EmbedCommitVerify, TryCommitVerify};
+
+        fn check<D: dbc::Proof>(
+            anchor: &Anchor<mpc::MerkleProof, D>,
+            tx: &Tx,
+            protocol_id: ProtocolId,
+            message: Message,
+        ) {
+            let observed =
+                <[u8; 32]>::try_from(&tx.outputs[0].script_pubkey[2..]).expect("32-byte value");
+            assert!(anchor.verify(protocol_id, message, tx).is_ok());
+            assert!(anchor.verify_against(protocol_id, message, observed));
+
+            let other_message = Message::from([2u8; 32]);
+            assert!(anchor.verify(protocol_id, other_message, tx).is_err());
+            assert!(!anchor.verify_against(protocol_id, other_message, observed));
+
+            let mut wrong = observed;
+            wrong[0] ^= 0xFF;
+            assert!(!anchor.verify_against(protocol_id, message, wrong));
+            assert!(!anchor.verify_against(protocol_id, message, [0u8; 32]));
+        }
+
+        let protocol_id = ProtocolId::from([0x11; 32]);
+        let message = Message::from([1u8; 32]);
+        let source = mpc::MultiSource {
+            method: mpc::Method::Sha256t,
+            min_depth: 3,
+            messages: Confined::try_from_iter([(protocol_id, message)]).unwrap(),
+            static_entropy: Some(0xdead_beef),
+        };
+        let block = mpc::MerkleBlock::from(mpc::MerkleTree::try_commit(&source).unwrap());
+        let mpc_proof = block.to_merkle_proof(protocol_id).unwrap();
+        let commitment = mpc_proof.convolve(protocol_id, message).unwrap();
+        let tx_with = |script_pubkey: ScriptPubkey| Tx {
+            version: TxVer::V2,
+            inputs: none!(),
+            outputs: VarIntArray::try_from(vec![TxOut::new(script_pubkey, 0u64)]).unwrap(),
+            lock_time: LockTime::ZERO,
+        };
+
+        // opret
+        let mut tx = tx_with(ScriptPubkey::from_unsafe(vec![OP_RETURN]));
+        let proof = tx.embed_commit(&commitment).unwrap();
+        check(&Anchor::new(mpc_proof.clone(), proof), &tx, protocol_id, message);
+        let anchor = Anchor::new(mpc_proof.clone(), DbcProof::from(proof));
+        assert_eq!(anchor.method, Method::OpretFirst);
+        check(&anchor, &tx, protocol_id, message);
+
+        // tapret
+        let TapretProof { internal_pk, .. } = tapret_anchor(0).dbc_proof;
+        let (output_pk, _) = internal_pk.to_output_pk_with_root(None);
+        let tx = tx_with(output_pk.to_script_pubkey());
+        let (tx, proof) = tx
+            .convolve_commit(&tapret_anchor(0).dbc_proof, &commitment)
+            .unwrap();
+        check(&Anchor::new(mpc_proof.clone(), proof.clone()), &tx, protocol_id, message);
+        let anchor = Anchor::new(mpc_proof, DbcProof::from(proof));
+        assert_eq!(anchor.method, Method::TapretFirst);
+        check(&anchor, &tx, protocol_id, message);
+    }
+}
@@ -51,6 +51,18 @@ extern crate commit_verify;
 /// Name of the strict type library generated from the data types in this crate.
 pub const LIB_NAME_BPCORE: &str = "BPCore";
 
+/// Maximum number of outputs in a consensus-valid transaction: the block weight
+/// limit of 4 000 000 WU divided by the weight of the smallest possible output
+/// (9 bytes, 36 WU).
+///
+/// Used as the default limit for the number of transaction outputs scanned
+/// when looking for a commitment.
+pub const MAX_TX_OUTPUTS: usize = 111_111;
+
+#[cfg(test)]
+#[macro_use]
+mod test_helpers;
+
 pub mod anchor;
 pub mod keytweak;
 pub mod opret;
@@ -59,4 +71,7 @@ pub mod tapret;
 mod proof;
 
 pub use anchor::Anchor;
-pub use proof::{DbcMethod, Method, MethodParseError, Proof};
+pub use proof::{
+    supported_methods, supported_methods_bounded, CommitmentCost, DbcMethod, DbcProof,
+    DbcProofError, Method, MethodParseError, Proof, TooManyOutputs,
+};
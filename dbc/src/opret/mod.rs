@@ -25,6 +25,9 @@ mod tx;
 mod txout;
 mod spk;
 
+pub use tx::{opret_commitment, opret_commitment_bounded};
+
+use amplify::ByteArray;
 use bc::Tx;
 use commit_verify::mpc::Commitment;
 use commit_verify::{CommitmentProtocol, EmbedCommitVerify, EmbedVerifyError};
@@ -54,6 +57,10 @@ pub enum OpretError {
     /// first OP_RETURN output inside the transaction already contains some
     /// data.
     InvalidOpretScript,
+
+    /// transaction has too many outputs ({0}) to be scanned for the
+    /// commitment.
+    TooManyOutputs(usize),
 }
 
 /// Empty type for use inside [`crate::Anchor`] for opret commitment scheme.
@@ -72,9 +79,12 @@ impl StrictDeserialize for OpretProof {}
 
 impl Proof<Method> for OpretProof {
     type Error = EmbedVerifyError<OpretError>;
-    const METHOD: Method = Method::OpretFirst;
+
+    fn method(&self) -> Method { Method::OpretFirst }
 
     fn verify(&self, msg: &Commitment, tx: &Tx) -> Result<(), EmbedVerifyError<OpretError>> {
         tx.verify(msg, self)
     }
+
+    fn commitment_value(&self, msg: &Commitment) -> Option<[u8; 32]> { Some(msg.to_byte_array()) }
 }
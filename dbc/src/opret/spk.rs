@@ -19,7 +19,7 @@
 // See the License for the specific language governing permissions and
 // limitations under the License.
 
-use bc::opcodes::OP_RETURN;
+use bc::opcodes::{OP_PUSHBYTES_32, OP_RETURN};
 use bc::ScriptPubkey;
 use commit_verify::mpc::Commitment;
 use commit_verify::{EmbedCommitProof, EmbedCommitVerify, EmbedVerifyError};
@@ -34,7 +34,8 @@ impl EmbedCommitProof<Commitment, ScriptPubkey, OpretFirst> for OpretProof {
         if !commit_container.is_op_return() {
             return Err(OpretError::NoOpretOutput.into());
         }
-        if commit_container.len() != 34 {
+        // The whole script must be `OP_RETURN OP_PUSHBYTES_32 <32 bytes>`
+        if commit_container.len() != 34 || commit_container[1] != OP_PUSHBYTES_32 {
             return Err(OpretError::InvalidOpretScript.into());
         }
         Ok(ScriptPubkey::from_unsafe(vec![OP_RETURN]))
@@ -19,17 +19,69 @@
 // See the License for the specific language governing permissions and
 // limitations under the License.
 
+use bc::opcodes::OP_PUSHBYTES_32;
 use bc::Tx;
 use commit_verify::mpc::Commitment;
 use commit_verify::{EmbedCommitProof, EmbedCommitVerify, EmbedVerifyError};
 
 use super::{OpretError, OpretFirst, OpretProof};
+use crate::MAX_TX_OUTPUTS;
+
+/// Extracts opret commitment from the first OP_RETURN output of the
+/// transaction.
+///
+/// Only the first OP_RETURN output is considered: if it is not exactly
+/// `OP_RETURN OP_PUSHBYTES_32 <32 bytes>` the commitment is invalid, even if
+/// some of the following OP_RETURN outputs have a valid form.
+///
+/// Transactions with more than [`MAX_TX_OUTPUTS`] outputs are rejected; use
+/// [`opret_commitment_bounded`] to apply a stricter limit.
+///
+/// # Errors
+///
+/// - [`OpretError::TooManyOutputs`] if the transaction has more outputs than
+///   allowed by the consensus;
+/// - [`OpretError::NoOpretOutput`] if the transaction has no OP_RETURN outputs;
+/// - [`OpretError::InvalidOpretScript`] if the first OP_RETURN output doesn't
+///   contain a commitment.
+#[inline]
+pub fn opret_commitment(tx: &Tx) -> Result<[u8; 32], OpretError> {
+    opret_commitment_bounded(tx, MAX_TX_OUTPUTS)
+}
+
+/// Version of [`opret_commitment`] rejecting transactions with more than
+/// `max_outputs` outputs before scanning them, allowing to bound the work
+/// performed per transaction.
+///
+/// # Errors
+///
+/// Same as for [`opret_commitment`], with [`OpretError::TooManyOutputs`]
+/// returned when the number of outputs exceeds `max_outputs`.
+pub fn opret_commitment_bounded(tx: &Tx, max_outputs: usize) -> Result<[u8; 32], OpretError> {
+    if tx.outputs.len() > max_outputs {
+        return Err(OpretError::TooManyOutputs(tx.outputs.len()));
+    }
+    let script_pubkey = &tx
+        .outputs()
+        .find(|txout| txout.script_pubkey.is_op_return())
+        .ok_or(OpretError::NoOpretOutput)?
+        .script_pubkey;
+    if script_pubkey.len() != 34 || script_pubkey[1] != OP_PUSHBYTES_32 {
+        return Err(OpretError::InvalidOpretScript);
+    }
+    let mut commitment = [0u8; 32];
+    commitment.copy_from_slice(&script_pubkey[2..]);
+    Ok(commitment)
+}
 
 impl EmbedCommitProof<Commitment, Tx, OpretFirst> for OpretProof {
     fn restore_original_container(
         &self,
         commit_container: &Tx,
     ) -> Result<Tx, EmbedVerifyError<OpretError>> {
+        if commit_container.outputs.len() > MAX_TX_OUTPUTS {
+            return Err(OpretError::TooManyOutputs(commit_container.outputs.len()).into());
+        }
         let mut tx = commit_container.clone();
         for txout in &mut tx.outputs {
             if txout.script_pubkey.is_op_return() {
@@ -46,6 +98,9 @@ impl EmbedCommitVerify<Commitment, OpretFirst> for Tx {
     type CommitError = OpretError;
 
     fn embed_commit(&mut self, msg: &Commitment) -> Result<Self::Proof, Self::CommitError> {
+        if self.outputs.len() > MAX_TX_OUTPUTS {
+            return Err(OpretError::TooManyOutputs(self.outputs.len()));
+        }
         for txout in &mut self.outputs {
             if txout.script_pubkey.is_op_return() {
                 return txout.script_pubkey.embed_commit(msg);
@@ -54,3 +109,137 @@ impl EmbedCommitVerify<Commitment, OpretFirst> for Tx {
         Err(OpretError::NoOpretOutput)
     }
 }
+
+#[cfg(test)]
+mod test {
+    use bc::opcodes::{OP_NOP, OP_PUSHBYTES_1, OP_RETURN};
+    use bc::{LockTime, ScriptPubkey, TxOut, TxVer, VarIntArray};
+
+    use super::*;
+
+    fn tx_with(scripts: impl IntoIterator<Item = ScriptPubkey>) -> Tx {
+        let outputs = scripts
+            .into_iter()
+            .map(|script_pubkey| TxOut::new(script_pubkey, 0u64))
+            .collect::<Vec<_>>();
+        Tx {
+            version: TxVer::V2,
+            inputs: none!(),
+            outputs: VarIntArray::try_from(outputs).unwrap(),
+            lock_time: LockTime::ZERO,
+        }
+    }
+
+    #[test]
+    fn first_opret() {
+        let tx = tx_with([
+            ScriptPubkey::p2pkh([0u8; 20]),
+            ScriptPubkey::op_return(&[1u8; 32]),
+            ScriptPubkey::op_return(&[2u8; 32]),
+        ]);
+        assert_eq!(opret_commitment(&tx), Ok([1u8; 32]));
+    }
+
+    #[test]
+    fn output_limit() {
+        let mut scripts = vec![ScriptPubkey::p2pkh([0u8; 20]); 9];
+        scripts.push(ScriptPubkey::op_return(&[1u8; 32]));
+        let tx = tx_with(scripts);
+        assert_eq!(opret_commitment_bounded(&tx, 10), Ok([1u8; 32]));
+        assert_eq!(opret_commitment_bounded(&tx, 9), Err(OpretError::TooManyOutputs(10)));
+        assert_eq!(opret_commitment_bounded(&tx_with([]), 0), Err(OpretError::NoOpretOutput));
+
+        let mut scripts = vec![ScriptPubkey::new(); MAX_TX_OUTPUTS];
+        scripts.push(ScriptPubkey::op_return(&[1u8; 32]));
+        let mut tx = tx_with(scripts);
+        assert_eq!(opret_commitment(&tx), Err(OpretError::TooManyOutputs(MAX_TX_OUTPUTS + 1)));
+        assert_eq!(
+            tx.embed_commit(&Commitment::from([1u8; 32])),
+            Err(OpretError::TooManyOutputs(MAX_TX_OUTPUTS + 1))
+        );
+    }
+
+    #[test]
+    fn no_opret() {
+        let tx = tx_with([ScriptPubkey::p2pkh([0u8; 20])]);
+        assert_eq!(opret_commitment(&tx), Err(OpretError::NoOpretOutput));
+    }
+
+    #[test]
+    fn no_outputs() {
+        let msg = Commitment::from([1u8; 32]);
+        let mut tx = tx_with([]);
+        assert_eq!(opret_commitment(&tx), Err(OpretError::NoOpretOutput));
+        assert!(tx.verify(&msg, &OpretProof::default()).is_err());
+        assert_eq!(tx.embed_commit(&msg), Err(OpretError::NoOpretOutput));
+    }
+
+    #[test]
+    fn leading_non_commitment_opret() {
+        let tx = tx_with([ScriptPubkey::op_return(b"memo"), ScriptPubkey::op_return(&[1u8; 32])]);
+        assert_eq!(opret_commitment(&tx), Err(OpretError::InvalidOpretScript));
+
+        let tx = tx_with([ScriptPubkey::op_return(&[]), ScriptPubkey::op_return(&[1u8; 32])]);
+        assert_eq!(opret_commitment(&tx), Err(OpretError::InvalidOpretScript));
+
+        // 34-byte script with a non-32-byte push
+        let mut script = vec![0x6a, 0x4c, 0x1f];
+        script.extend([1u8; 31]);
+        let tx = tx_with([ScriptPubkey::from_unsafe(script)]);
+        assert_eq!(opret_commitment(&tx), Err(OpretError::InvalidOpretScript));
+    }
+
+    #[test]
+    fn short_opret() {
+        let mut script = vec![OP_RETURN, OP_PUSHBYTES_32];
+        script.extend([1u8; 33]);
+        for len in [1, 2, 33, 35] {
+            let tx = tx_with([ScriptPubkey::from_unsafe(script[..len].to_vec())]);
+            assert_eq!(opret_commitment(&tx), Err(OpretError::InvalidOpretScript));
+            assert!(OpretProof::default()
+                .restore_original_container(&tx)
+                .is_err());
+        }
+    }
+
+    #[test]
+    fn non_canonical_opret() {
+        let msg = Commitment::from([1u8; 32]);
+
+        let mut script = vec![OP_RETURN, OP_PUSHBYTES_32];
+        script.extend([1u8; 32]);
+        script.push(OP_NOP);
+        let tx = tx_with([ScriptPubkey::from_unsafe(script)]);
+        assert_eq!(opret_commitment(&tx), Err(OpretError::InvalidOpretScript));
+        assert!(tx.verify(&msg, &OpretProof::default()).is_err());
+
+        let mut script = vec![OP_RETURN, OP_PUSHBYTES_32];
+        script.extend([1u8; 32]);
+        script.extend([OP_PUSHBYTES_1, 0x01]);
+        let tx = tx_with([ScriptPubkey::from_unsafe(script)]);
+        assert_eq!(opret_commitment(&tx), Err(OpretError::InvalidOpretScript));
+        assert!(tx.verify(&msg, &OpretProof::default()).is_err());
+
+        // 34-byte script with an opcode preceding the push
+        let mut script = vec![OP_RETURN, OP_NOP, 0x1f];
+        script.extend([1u8; 31]);
+        let tx = tx_with([ScriptPubkey::from_unsafe(script)]);
+        assert_eq!(opret_commitment(&tx), Err(OpretError::InvalidOpretScript));
+        assert!(OpretProof::default()
+            .restore_original_container(&tx)
+            .is_err());
+    }
+
+    #[test]
+    fn commit_verify() {
+        let msg = Commitment::from([1u8; 32]);
+        let opret = ScriptPubkey::from_unsafe(vec![OP_RETURN]);
+        let tx = tx_with([ScriptPubkey::p2pkh([0u8; 20]), opret.clone()]);
+        let (tx, proof) = assert_commit_verify!(embed, tx, msg);
+        assert_eq!(opret_commitment(&tx), Ok([1u8; 32]));
+        assert_eq!(proof, OpretProof::default());
+
+        let (script_pubkey, _) = assert_commit_verify!(embed, opret, msg);
+        assert_eq!(tx.outputs[1].script_pubkey, script_pubkey);
+    }
+}
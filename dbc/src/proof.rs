@@ -20,19 +20,28 @@
 // limitations under the License.
 
 use std::error::Error;
-use std::fmt::Debug;
+use std::fmt::{self, Debug, Display, Formatter, Write};
 use std::str::FromStr;
 
-use bc::Tx;
-use commit_verify::mpc;
+use bc::{Tx, VBytes, WeightUnits};
+use commit_verify::{mpc, ConvolveVerifyError, EmbedVerifyError};
 use strict_encoding::{StrictDecode, StrictDeserialize, StrictDumb, StrictEncode, StrictSerialize};
 
-use crate::LIB_NAME_BPCORE;
+use crate::opret::{OpretError, OpretProof};
+use crate::tapret::TapretProof;
+use crate::{LIB_NAME_BPCORE, MAX_TX_OUTPUTS};
 
 /// Trait defining DBC method - or enumberation of allowed DBC methods used by
 /// proofs, single-use-seals etc.
+///
+/// Together with [`Proof`] this forms an extension point for commitment
+/// schemes: a new scheme defines its method type and a proof type
+/// implementing [`Proof`] for that method, and can be used with
+/// [`crate::Anchor`] and seals without any changes to the built-in opret and
+/// tapret methods.
 pub trait DbcMethod:
     Copy
+    + Debug
     + Eq
     + Ord
     + std::hash::Hash
@@ -42,11 +51,38 @@ pub trait DbcMethod:
 {
 }
 
-/// wrong deterministic bitcoin commitment closing method id '{0}'.
-#[derive(Clone, PartialEq, Eq, Debug, Display, Error, From)]
-#[display(doc_comments)]
+/// Error parsing deterministic bitcoin commitment method.
+///
+/// The error holds the original string; its display representation is
+/// truncated to [`MethodParseError::MAX_DISPLAY_LEN`] characters and has all
+/// non-printable characters escaped, such that untrusted input can't pollute
+/// logs.
+#[derive(Clone, PartialEq, Eq, Debug, Error, From)]
 pub struct MethodParseError(pub String);
 
+impl MethodParseError {
+    /// Maximal number of characters from the parsed string which are shown
+    /// in the error message.
+    pub const MAX_DISPLAY_LEN: usize = 32;
+}
+
+impl Display for MethodParseError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        f.write_str("wrong deterministic bitcoin commitment closing method id '")?;
+        for c in self.0.chars().take(Self::MAX_DISPLAY_LEN) {
+            if c.is_control() {
+                write!(f, "{}", c.escape_debug())?;
+            } else {
+                f.write_char(c)?;
+            }
+        }
+        if self.0.chars().nth(Self::MAX_DISPLAY_LEN).is_some() {
+            f.write_char('…')?;
+        }
+        f.write_char('\'')
+    }
+}
+
 /// Method of DBC construction.
 #[derive(Copy, Clone, Ord, PartialOrd, Eq, PartialEq, Hash, Debug, Display)]
 #[cfg_attr(
@@ -72,6 +108,114 @@ pub enum Method {
 
 impl DbcMethod for Method {}
 
+impl Method {
+    /// All DBC methods supported by this library, in the order of their
+    /// consensus tags.
+    pub const ALL: &'static [Method] = &[Method::OpretFirst, Method::TapretFirst];
+
+    /// Returns number of DBC methods supported by this library.
+    #[inline]
+    pub const fn count() -> usize { Self::ALL.len() }
+
+    /// Returns approximate transaction weight added by the commitment made
+    /// with this method, for fee planning.
+    ///
+    /// The estimation assumes 32-byte commitment payload and:
+    /// - for [`Method::OpretFirst`], that a new zero-value `OP_RETURN` output
+    ///   is added to the transaction (reusing an existing bare `OP_RETURN`
+    ///   output costs less); a possible increase of the output count length
+    ///   prefix is ignored;
+    /// - for [`Method::TapretFirst`], the worst case of spending the committed
+    ///   output via script path, when the control block gets an additional
+    ///   32-byte merkle path node. Key path spending has no overhead, and the
+    ///   commitment transaction itself doesn't change in size.
+    pub fn commitment_overhead(&self) -> CommitmentCost {
+        match self {
+            Method::OpretFirst => CommitmentCost {
+                // value, script length and `OP_RETURN OP_PUSHBYTES_32 <32 bytes>`
+                commit_tx: WeightUnits::no_discount(8 + 1 + 34),
+                spending: WeightUnits::witness_discount(0),
+            },
+            Method::TapretFirst => CommitmentCost {
+                commit_tx: WeightUnits::no_discount(0),
+                spending: WeightUnits::witness_discount(32),
+            },
+        }
+    }
+}
+
+/// Approximate weight added by a deterministic bitcoin commitment, see
+/// [`Method::commitment_overhead`].
+#[derive(Copy, Clone, Eq, PartialEq, Hash, Debug)]
+pub struct CommitmentCost {
+    /// Weight added to the transaction containing the commitment.
+    pub commit_tx: WeightUnits,
+    /// Weight added to the transaction spending the output containing the
+    /// commitment.
+    pub spending: WeightUnits,
+}
+
+impl CommitmentCost {
+    /// Returns total weight added by the commitment across the commitment and
+    /// spending transactions.
+    #[inline]
+    pub fn total(&self) -> WeightUnits { self.commit_tx + self.spending }
+
+    /// Returns total overhead of the commitment in virtual bytes.
+    #[inline]
+    pub fn vbytes(&self) -> VBytes { VBytes::from(self.total()) }
+}
+
+/// transaction has too many outputs ({0}) to be scanned for the commitment.
+#[derive(Copy, Clone, Eq, PartialEq, Hash, Debug, Display, Error)]
+#[display(doc_comments)]
+pub struct TooManyOutputs(pub usize);
+
+/// Returns DBC methods which can be used to commit to the transaction in its
+/// current form, in the order of their consensus tags.
+///
+/// [`Method::OpretFirst`] is supported if the transaction has no OP_RETURN
+/// outputs (and thus one can be added) or its first OP_RETURN output is a
+/// bare `OP_RETURN` without data. [`Method::TapretFirst`] is supported if the
+/// transaction contains a taproot output.
+///
+/// # Errors
+///
+/// If the transaction has more than [`MAX_TX_OUTPUTS`] outputs; use
+/// [`supported_methods_bounded`] to apply a stricter limit.
+#[inline]
+pub fn supported_methods(tx: &Tx) -> Result<Vec<Method>, TooManyOutputs> {
+    supported_methods_bounded(tx, MAX_TX_OUTPUTS)
+}
+
+/// Version of [`supported_methods`] rejecting transactions with more than
+/// `max_outputs` outputs before scanning them.
+///
+/// # Errors
+///
+/// If the transaction has more than `max_outputs` outputs.
+pub fn supported_methods_bounded(
+    tx: &Tx,
+    max_outputs: usize,
+) -> Result<Vec<Method>, TooManyOutputs> {
+    if tx.outputs.len() > max_outputs {
+        return Err(TooManyOutputs(tx.outputs.len()));
+    }
+    let opret = match tx.outputs().find(|txout| txout.script_pubkey.is_op_return()) {
+        None => true,
+        Some(txout) => txout.script_pubkey.len() == 1,
+    };
+    let tapret = tx.outputs().any(|txout| txout.script_pubkey.is_p2tr());
+    Ok(Method::ALL
+        .iter()
+        .copied()
+        .filter(|method| match method {
+            Method::OpretFirst => opret,
+            Method::TapretFirst => tapret,
+        })
+        .collect())
+}
+
 impl FromStr for Method {
     type Err = MethodParseError;
 
@@ -92,8 +236,294 @@ pub trait Proof<M: DbcMethod = Method>:
     type Error: Error;
 
     /// Returns DBC method used by the proof.
-    const METHOD: M;
+    fn method(&self) -> M;
 
     /// Verifies DBC proof against the provided transaction.
     fn verify(&self, msg: &mpc::Commitment, tx: &Tx) -> Result<(), Self::Error>;
+
+    /// Computes 32-byte value which must be present in the transaction output
+    /// committing to the message under this proof (OP_RETURN payload for
+    /// opret and output key for tapret).
+    ///
+    /// Returns `None` if a valid commitment to the message can't be created
+    /// using this proof. Default implementation always returns `None`, such
+    /// that proof types not supporting the procedure can't be verified with
+    /// [`crate::Anchor::verify_against`].
+    fn commitment_value(&self, _msg: &mpc::Commitment) -> Option<[u8; 32]> { None }
+}
+
+/// Errors verifying [`DbcProof`].
+#[derive(Clone, Eq, PartialEq, Debug, Display, Error, From)]
+#[display(inner)]
+pub enum DbcProofError {
+    /// Opret commitment verification error.
+    #[from]
+    Opret(EmbedVerifyError<OpretError>),
+
+    /// Tapret commitment verification error.
+    #[from]
+    Tapret(ConvolveVerifyError),
+}
+
+/// Deterministic bitcoin commitment proof for any of the supported methods.
+///
+/// Strict encoding tags of the variants match the [`Method`] values, such that
+/// proofs for methods added in the future get new tags and proofs with tags
+/// unknown to a decoder fail to decode instead of being misparsed.
+///
+/// Implements [`Proof`] with the method taken from the proof variant, thus
+/// anchors with proofs of different methods can be kept in a single
+/// collection as `Anchor<_, DbcProof>`.
+#[derive(Clone, Ord, PartialOrd, Eq, PartialEq, Hash, Debug, From)]
+#[derive(StrictType, StrictDumb, StrictEncode, StrictDecode)]
+#[strict_type(lib = LIB_NAME_BPCORE, tags = custom, dumb = Self::Opret(default!()))]
+#[cfg_attr(
+    feature = "serde",
+    derive(Serialize, Deserialize),
+    serde(crate = "serde_crate", tag = "method", content = "proof")
+)]
+pub enum DbcProof {
+    /// Opret commitment proof.
+    #[from]
+    #[strict_type(tag = 0x00)]
+    #[cfg_attr(feature = "serde", serde(rename = "opret1st"))]
+    Opret(OpretProof),
+
+    /// Tapret commitment proof.
+    #[from]
+    #[strict_type(tag = 0x01)]
+    #[cfg_attr(feature = "serde", serde(rename = "tapret1st"))]
+    Tapret(TapretProof),
+}
+
+impl StrictSerialize for DbcProof {}
+impl StrictDeserialize for DbcProof {}
+
+impl Proof<Method> for DbcProof {
+    type Error = DbcProofError;
+
+    fn method(&self) -> Method {
+        match self {
+            DbcProof::Opret(proof) => proof.method(),
+            DbcProof::Tapret(proof) => proof.method(),
+        }
+    }
+
+    fn verify(&self, msg: &mpc::Commitment, tx: &Tx) -> Result<(), DbcProofError> {
+        match self {
+            DbcProof::Opret(proof) => proof.verify(msg, tx).map_err(DbcProofError::from),
+            DbcProof::Tapret(proof) => proof.verify(msg, tx).map_err(DbcProofError::from),
+        }
+    }
+
+    fn commitment_value(&self, msg: &mpc::Commitment) -> Option<[u8; 32]> {
+        match self {
+            DbcProof::Opret(proof) => proof.commitment_value(msg),
+            DbcProof::Tapret(proof) => proof.commitment_value(msg),
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use amplify::confinement::Confined;
+    use amplify::ByteArray;
+    use bc::opcodes::{OP_PUSHBYTES_32, OP_RETURN};
+    use bc::{LockTime, ScriptPubkey, TapNodeHash, TxOut, TxVer, VarIntArray};
+    use commit_verify::mpc::{Commitment, Message, ProtocolId};
+
+    use super::*;
+    use crate::anchor::VerifyError;
+    use crate::tapret::TapretPathProof;
+    use crate::test_helpers::internal_pk;
+    use crate::Anchor;
+
+    /// Custom DBC method committing with a bare 32-byte push in the first
+    /// transaction output.
+    #[derive(Copy, Clone, Ord, PartialOrd, Eq, PartialEq, Hash, Debug)]
+    #[derive(StrictType, StrictDumb, StrictEncode, StrictDecode)]
+    #[strict_type(lib = "Test", tags = repr, into_u8, try_from_u8)]
+    #[repr(u8)]
+    enum PushMethod {
+        #[strict_type(dumb)]
+        PushFirst = 0x00,
+    }
+
+    impl DbcMethod for PushMethod {}
+
+    #[derive(Copy, Clone, Ord, PartialOrd, Eq, PartialEq, Hash, Debug, Default)]
+    #[derive(StrictType, StrictEncode, StrictDecode)]
+    #[strict_type(lib = "Test")]
+    struct PushProof(());
+
+    impl StrictSerialize for PushProof {}
+    impl StrictDeserialize for PushProof {}
+
+    /// the first transaction output doesn't commit to the message.
+    #[derive(Copy, Clone, Eq, PartialEq, Debug, Display, Error)]
+    #[display(doc_comments)]
+    struct NoPushCommitment;
+
+    impl Proof<PushMethod> for PushProof {
+        type Error = NoPushCommitment;
+
+        fn method(&self) -> PushMethod { PushMethod::PushFirst }
+
+        fn verify(&self, msg: &Commitment, tx: &Tx) -> Result<(), NoPushCommitment> {
+            let value = self.commitment_value(msg).ok_or(NoPushCommitment)?;
+            let mut script = vec![OP_PUSHBYTES_32];
+            script.extend(value);
+            match tx.outputs.first() {
+                Some(txout) if txout.script_pubkey.as_slice() == &script[..] => Ok(()),
+                _ => Err(NoPushCommitment),
+            }
+        }
+
+        fn commitment_value(&self, msg: &Commitment) -> Option<[u8; 32]> {
+            Some(msg.to_byte_array())
+        }
+    }
+
+    #[test]
+    fn custom_method() {
+        use commit_verify::TryCommitVerify;
+
+        let protocol_id = ProtocolId::from([0x11; 32]);
+        let message = Message::from([7u8; 32]);
+        let source = mpc::MultiSource {
+            method: mpc::Method::Sha256t,
+            min_depth: 3,
+            messages: Confined::try_from_iter([(protocol_id, message)]).unwrap(),
+            static_entropy: Some(0xdead_beef),
+        };
+        let block = mpc::MerkleBlock::from(mpc::MerkleTree::try_commit(&source).unwrap());
+        let mpc_proof = block.to_merkle_proof(protocol_id).unwrap();
+
+        let anchor = Anchor::<_, PushProof, PushMethod>::new(mpc_proof.clone(), default!());
+        assert_eq!(anchor.method, PushMethod::PushFirst);
+        let msg = anchor.convolve(protocol_id, message).unwrap();
+        let mut script = vec![OP_PUSHBYTES_32];
+        script.extend(msg.to_byte_array());
+        let tx = Tx {
+            version: TxVer::V2,
+            inputs: none!(),
+            outputs: VarIntArray::try_from(vec![TxOut::new(
+                ScriptPubkey::from_unsafe(script),
+                0u64,
+            )])
+            .unwrap(),
+            lock_time: LockTime::ZERO,
+        };
+
+        assert_eq!(anchor.verify(protocol_id, message, &tx), Ok(msg));
+        assert_eq!(
+            anchor.verify(protocol_id, Message::from([8u8; 32]), &tx),
+            Err(VerifyError::Dbc(NoPushCommitment))
+        );
+        // built-in methods don't accept the custom commitment
+        let opret = Anchor::<_, OpretProof>::new(mpc_proof, default!());
+        assert!(matches!(opret.verify(protocol_id, message, &tx), Err(VerifyError::Dbc(_))));
+    }
+
+    #[test]
+    fn dbc_proof_encoding() {
+        let opret = DbcProof::from(OpretProof::default());
+        assert_eq!(opret.method(), Method::OpretFirst);
+        let data = opret.to_strict_serialized::<1024>().unwrap();
+        assert_eq!(data.as_slice(), &[Method::OpretFirst as u8]);
+        assert_eq!(DbcProof::from_strict_serialized::<1024>(data).unwrap(), opret);
+
+        let tapret = DbcProof::from(TapretProof {
+            path_proof: TapretPathProof::root(5),
+            internal_pk: internal_pk(),
+        });
+        assert_eq!(tapret.method(), Method::TapretFirst);
+        let data = tapret.to_strict_serialized::<1024>().unwrap();
+        assert_eq!(data[0], Method::TapretFirst as u8);
+        assert_eq!(DbcProof::from_strict_serialized::<1024>(data.clone()).unwrap(), tapret);
+
+        // proof for some future method is rejected
+        let mut future = data.into_inner();
+        future[0] = 0x02;
+        let future = Confined::try_from(future).unwrap();
+        assert!(DbcProof::from_strict_serialized::<1024>(future).is_err());
+    }
+
+    #[test]
+    fn methods_supported_by_tx() {
+        let p2wpkh = TxOut::new(ScriptPubkey::p2wpkh([0xA1; 20]), 1000u64);
+        let p2tr = TxOut::new(ScriptPubkey::p2tr(internal_pk(), None::<TapNodeHash>), 1000u64);
+        let op_return = TxOut::new(ScriptPubkey::from_unsafe(vec![OP_RETURN]), 0u64);
+        let op_return_data = TxOut::new(ScriptPubkey::op_return(&[0xA1; 32]), 0u64);
+        let tx = |outputs: Vec<TxOut>| Tx {
+            version: TxVer::V2,
+            inputs: none!(),
+            outputs: VarIntArray::try_from(outputs).unwrap(),
+            lock_time: LockTime::ZERO,
+        };
+
+        let methods = |outputs: Vec<TxOut>| supported_methods(&tx(outputs)).unwrap();
+
+        assert_eq!(methods(vec![p2wpkh.clone()]), vec![Method::OpretFirst]);
+        assert_eq!(methods(vec![]), vec![Method::OpretFirst]);
+        assert_eq!(methods(vec![p2wpkh.clone(), op_return]), vec![Method::OpretFirst]);
+        assert_eq!(methods(vec![p2wpkh.clone(), op_return_data.clone()]), vec![]);
+        assert_eq!(methods(vec![p2wpkh.clone(), p2tr.clone()]), Method::ALL.to_vec());
+        assert_eq!(methods(vec![op_return_data, p2tr.clone()]), vec![Method::TapretFirst]);
+
+        let bounded = tx(vec![p2wpkh, p2tr.clone()]);
+        assert_eq!(supported_methods_bounded(&bounded, 2), Ok(Method::ALL.to_vec()));
+        assert_eq!(supported_methods_bounded(&bounded, 1), Err(TooManyOutputs(2)));
+
+        let mut outputs = vec![TxOut::new(ScriptPubkey::new(), 0u64); MAX_TX_OUTPUTS];
+        outputs.push(p2tr);
+        assert_eq!(supported_methods(&tx(outputs)), Err(TooManyOutputs(MAX_TX_OUTPUTS + 1)));
+    }
+
+    #[test]
+    fn commitment_overhead() {
+        use bc::Weight;
+
+        let opret = Method::OpretFirst.commitment_overhead();
+        // 8 bytes value + 1 byte script length + 34 bytes script, no witness discount
+        assert_eq!(opret.commit_tx.to_u32(), (8 + 1 + 34) * 4);
+        assert_eq!(opret.spending.to_u32(), 0);
+        assert_eq!(opret.vbytes().to_u32(), 43);
+        let txout = TxOut::new(ScriptPubkey::op_return(&[0xA1; 32]), 0u64);
+        assert_eq!(opret.commit_tx, txout.weight_units());
+
+        let tapret = Method::TapretFirst.commitment_overhead();
+        assert_eq!(tapret.commit_tx.to_u32(), 0);
+        assert_eq!(tapret.spending.to_u32(), 32);
+        assert_eq!(tapret.vbytes().to_u32(), 8);
+        assert!(tapret.total() < opret.total());
+    }
+
+    #[test]
+    fn method_enumeration() {
+        assert_eq!(Method::ALL.len(), Method::count());
+        for (no, method) in Method::ALL.iter().enumerate() {
+            // exhaustive match breaks once a new method is added, so `ALL` is revisited
+            let tag = match method {
+                Method::OpretFirst => 0x00,
+                Method::TapretFirst => 0x01,
+            };
+            assert_eq!(*method as u8, tag);
+            assert_eq!(no, tag as usize);
+            assert_eq!(Method::from_str(&method.to_string()), Ok(*method));
+        }
+    }
+
+    #[test]
+    fn method_parse_error_display() {
+        assert_eq!(
+            Method::from_str("tapret").unwrap_err().to_string(),
+            "wrong deterministic bitcoin commitment closing method id 'tapret'"
+        );
+
+        let err = Method::from_str(&format!("opret\n\x1b[31m{}", "ы".repeat(64))).unwrap_err();
+        let expected = format!("'opret\\n\\u{{1b}}[31m{}…'", "ы".repeat(21));
+        assert!(err.to_string().ends_with(&expected));
+        assert!(!err.to_string().contains('\n'));
+    }
 }
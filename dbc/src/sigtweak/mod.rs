@@ -27,3 +27,330 @@
 //! **Convolve-commit:**
 //! c) `psbt::Input, PrivateKey, Msg -> psbt::Input'`;
 //! d) `psbt::Input, KeyPair, Msg -> psbt::Input'`;
+//!
+//! Currently, only the sign-commit procedure for BIP-340 signatures spending
+//! taproot outputs via the key path is implemented
+//! ([`sign_taproot_key_spend`]). The commitment is embedded into the
+//! signature nonce ("sign-to-contract"): the original nonce point is tweaked
+//! with a tagged hash of itself and the message, and the tweaked point is used
+//! as the signature nonce. The resulting signature is a regular BIP-340
+//! signature valid under BIP-341 rules; the commitment is verified with the
+//! original nonce point kept as [`SigtweakProof`].
+//!
+//! # Signing procedure
+//!
+//! Given an internal key pair, an optional script tree merkle root, a BIP-341
+//! signature hash `m`, a committed message `msg` and 32 bytes of auxiliary
+//! randomness `a`, the signature is computed as follows (`n` is the curve
+//! order, `hash_tag` is a BIP-340 tagged hash, other notation follows
+//! BIP-340):
+//!
+//! 1. The internal key pair is tweaked with [`InternalPk::tap_tweak`]; `P` is
+//!    the resulting x-only output key and `d` is its secret key, negated if
+//!    the output key has odd `y` coordinate.
+//! 2. The original nonce `k0` is produced by the BIP-340 nonce function from
+//!    `d`, `P` and `m`, using `hash_tag(a || msg)` with [`SIGTWEAK_AUX_TAG`]
+//!    as the auxiliary randomness. `R0 = k0·G` is the original nonce point.
+//! 3. `t = int(hash_tag(cbytes(R0) || msg)) mod n` with [`SIGTWEAK_TAG`] is
+//!    the commitment tweak; `k = (k0 + t) mod n` is the signature nonce and
+//!    `R = k·G`. If `R` has odd `y` coordinate, `k` is negated.
+//! 4. The signature is `bytes(R) || bytes((k + e·d) mod n)`, where `e` is the
+//!    BIP-340 challenge for `R`, `P` and `m`.
+//!
+//! Mixing `msg` into the auxiliary randomness ensures that signing the same
+//! signature hash with commitments to different messages never reuses the
+//! original nonce, which would reveal the secret key since the commitment
+//! tweak `t` is public.
+
+use bc::{CompressedPk, InternalPk, TapNodeHash};
+use commit_verify::{mpc, DigestExt, Sha256};
+use secp256k1::constants::CURVE_ORDER;
+use secp256k1::schnorr::Signature;
+use secp256k1::{Keypair, Parity, PublicKey, Scalar, SecretKey, SECP256K1};
+use strict_encoding::{StrictDeserialize, StrictSerialize};
+
+use crate::LIB_NAME_BPCORE;
+
+/// Tag used for hashing the original nonce point together with the committed
+/// message, producing the commitment tweak.
+pub const SIGTWEAK_TAG: &str = "urn:lnp-bp:bip340:sigtweak#v1";
+
+/// Tag used for hashing the auxiliary randomness together with the committed
+/// message before it is passed to the BIP-340 nonce function.
+pub const SIGTWEAK_AUX_TAG: &str = "urn:lnp-bp:bip340:sigtweak-aux#v1";
+
+/// Errors creating signature with sigtweak commitment.
+#[derive(Copy, Clone, Eq, PartialEq, Hash, Debug, Display, Error)]
+#[display(doc_comments)]
+pub enum SigtweakError {
+    /// derived taproot tweak, nonce or signature value is not a valid
+    /// secp256k1 scalar; the signature must be created with a different
+    /// auxiliary randomness.
+    InvalidScalar,
+}
+
+/// Proof of the sigtweak commitment, containing the original (untweaked)
+/// signature nonce point.
+#[derive(Copy, Clone, Ord, PartialOrd, Eq, PartialEq, Hash, Debug)]
+#[derive(StrictType, StrictDumb, StrictEncode, StrictDecode)]
+#[strict_type(lib = LIB_NAME_BPCORE)]
+#[cfg_attr(
+    feature = "serde",
+    derive(Serialize, Deserialize),
+    serde(crate = "serde_crate", rename_all = "camelCase")
+)]
+pub struct SigtweakProof {
+    /// Original nonce point before it was tweaked with the commitment.
+    pub nonce_point: CompressedPk,
+}
+
+impl StrictSerialize for SigtweakProof {}
+impl StrictDeserialize for SigtweakProof {}
+
+impl SigtweakProof {
+    /// Computes nonce point tweaked with the commitment to the `msg`.
+    pub fn tweaked_nonce_point(&self, msg: &mpc::Commitment) -> Result<PublicKey, SigtweakError> {
+        self.nonce_point
+            .add_exp_tweak(SECP256K1, &nonce_tweak(*self.nonce_point, msg))
+            .map_err(|_| SigtweakError::InvalidScalar)
+    }
+
+    /// Verifies that the BIP-340 signature `sig` commits to the `msg`.
+    ///
+    /// NB: this checks only the commitment; the validity of the signature
+    /// itself must be checked separately.
+    pub fn verify(&self, sig: &Signature, msg: &mpc::Commitment) -> bool {
+        self.tweaked_nonce_point(msg)
+            .map(|point| point.x_only_public_key().0.serialize()[..] == sig.serialize()[..32])
+            .unwrap_or_default()
+    }
+}
+
+/// Creates BIP-340 signature of the `sighash` for a taproot key path spending
+/// of an output with the internal key `keypair` and an optional script tree
+/// `merkle_root`, committing to the `msg` with the signature nonce.
+///
+/// See the [module-level documentation](self) for the description of the
+/// procedure.
+///
+/// # Errors
+///
+/// If any of the derived values is not a valid secp256k1 scalar, which happens
+/// with a negligible probability.
+pub fn sign_taproot_key_spend(
+    keypair: &Keypair,
+    merkle_root: Option<TapNodeHash>,
+    sighash: [u8; 32],
+    msg: &mpc::Commitment,
+    aux_rand: [u8; 32],
+) -> Result<(Signature, SigtweakProof), SigtweakError> {
+    // BIP-341 output key
+    let internal_pk = InternalPk::from(keypair.x_only_public_key().0);
+    let output_keypair = keypair
+        .add_xonly_tweak(SECP256K1, &internal_pk.tap_tweak(merkle_root))
+        .map_err(|_| SigtweakError::InvalidScalar)?;
+    let (output_pk, parity) = output_keypair.x_only_public_key();
+    let mut seckey = SecretKey::from_keypair(&output_keypair);
+    if parity == Parity::Odd {
+        seckey = seckey.negate();
+    }
+
+    // BIP-340 nonce function with auxiliary randomness bound to the message
+    let mut engine = Sha256::from_tag(SIGTWEAK_AUX_TAG);
+    engine.input_raw(&aux_rand);
+    engine.input_raw(msg.as_slice());
+    let mut engine_aux = Sha256::from_tag(b"BIP0340/aux");
+    engine_aux.input_raw(&engine.finish());
+    let mut masked_key = seckey.secret_bytes();
+    for (byte, mask) in masked_key.iter_mut().zip(engine_aux.finish()) {
+        *byte ^= mask;
+    }
+    let mut engine = Sha256::from_tag(b"BIP0340/nonce");
+    engine.input_raw(&masked_key);
+    engine.input_raw(&output_pk.serialize());
+    engine.input_raw(&sighash);
+    let nonce = SecretKey::from_slice(&reduce(engine.finish()).to_be_bytes())
+        .map_err(|_| SigtweakError::InvalidScalar)?;
+    let nonce_point = PublicKey::from_secret_key(SECP256K1, &nonce);
+
+    // Nonce tweaked with the commitment
+    let mut nonce = nonce
+        .add_tweak(&nonce_tweak(nonce_point, msg))
+        .map_err(|_| SigtweakError::InvalidScalar)?;
+    let (nonce_x, nonce_parity) = PublicKey::from_secret_key(SECP256K1, &nonce).x_only_public_key();
+    if nonce_parity == Parity::Odd {
+        nonce = nonce.negate();
+    }
+
+    // BIP-340 signature: s = k + e * d
+    let mut engine = Sha256::from_tag(b"BIP0340/challenge");
+    engine.input_raw(&nonce_x.serialize());
+    engine.input_raw(&output_pk.serialize());
+    engine.input_raw(&sighash);
+    let s = seckey
+        .mul_tweak(&reduce(engine.finish()))
+        .and_then(|ed| ed.add_tweak(&Scalar::from(nonce)))
+        .map_err(|_| SigtweakError::InvalidScalar)?;
+
+    let mut sig = [0u8; 64];
+    sig[..32].copy_from_slice(&nonce_x.serialize());
+    sig[32..].copy_from_slice(&s.secret_bytes());
+    let sig = Signature::from_slice(&sig).expect("signature has correct length");
+    Ok((sig, SigtweakProof {
+        nonce_point: nonce_point.into(),
+    }))
+}
+
+fn nonce_tweak(nonce_point: PublicKey, msg: &mpc::Commitment) -> Scalar {
+    let mut engine = Sha256::from_tag(SIGTWEAK_TAG);
+    engine.input_raw(&nonce_point.serialize());
+    engine.input_raw(msg.as_slice());
+    reduce(engine.finish())
+}
+
+/// Interprets 32 bytes as a big-endian integer reduced modulo the curve order,
+/// as done by BIP-340 for hash values.
+fn reduce(mut bytes: [u8; 32]) -> Scalar {
+    if let Ok(scalar) = Scalar::from_be_bytes(bytes) {
+        return scalar;
+    }
+    // values not less than the curve order are less than its double, thus a
+    // single subtraction is sufficient
+    let mut borrow = 0i16;
+    for (byte, order) in bytes.iter_mut().zip(CURVE_ORDER).rev() {
+        let diff = *byte as i16 - order as i16 - borrow;
+        *byte = diff as u8;
+        borrow = (diff < 0) as i16;
+    }
+    Scalar::from_be_bytes(bytes).expect("reduced value is less than the curve order")
+}
+
+#[cfg(test)]
+mod test {
+    use amplify::hex::{FromHex, ToHex};
+    use bc::{IntoTapHash, LeafScript, TapScript};
+    use secp256k1::Message;
+
+    use super::*;
+
+    fn keypair() -> Keypair {
+        let seckey = SecretKey::from_slice(&[0xA5; 32]).unwrap();
+        Keypair::from_secret_key(SECP256K1, &seckey)
+    }
+
+    fn script_root() -> TapNodeHash {
+        let leaf = LeafScript::from_tap_script(TapScript::from_unsafe(vec![0x51]));
+        leaf.tap_leaf_hash().into_tap_hash()
+    }
+
+    #[test]
+    fn taproot_key_spend() {
+        let keypair = keypair();
+        let internal_pk = InternalPk::from(keypair.x_only_public_key().0);
+        let msg = mpc::Commitment::from([8u8; 32]);
+        let sighash = [0x5A; 32];
+
+        for merkle_root in [None, Some(script_root())] {
+            for aux_rand in [[0u8; 32], [1u8; 32]] {
+                let (sig, proof) =
+                    sign_taproot_key_spend(&keypair, merkle_root, sighash, &msg, aux_rand)
+                        .unwrap();
+
+                // valid BIP-341 key path signature
+                let (output_pk, _) = internal_pk.to_output_pk_with_root(merkle_root);
+                let digest = Message::from_digest(sighash);
+                assert!(SECP256K1.verify_schnorr(&sig, &digest, &output_pk).is_ok());
+                let (internal_xonly, _) = keypair.x_only_public_key();
+                assert!(SECP256K1.verify_schnorr(&sig, &digest, &internal_xonly).is_err());
+
+                // carrying the commitment
+                assert!(proof.verify(&sig, &msg));
+                assert!(!proof.verify(&sig, &mpc::Commitment::from([9u8; 32])));
+                let other_nonce = SecretKey::from_slice(&[1u8; 32]).unwrap();
+                let other_proof = SigtweakProof {
+                    nonce_point: PublicKey::from_secret_key(SECP256K1, &other_nonce).into(),
+                };
+                assert!(!other_proof.verify(&sig, &msg));
+            }
+        }
+    }
+
+    #[test]
+    fn nonce_bound_to_message() {
+        let sighash = [0x5A; 32];
+        let (sig1, proof1) =
+            sign_taproot_key_spend(&keypair(), None, sighash, &[8u8; 32].into(), [0u8; 32])
+                .unwrap();
+        let (sig2, proof2) =
+            sign_taproot_key_spend(&keypair(), None, sighash, &[9u8; 32].into(), [0u8; 32])
+                .unwrap();
+        assert_ne!(proof1.nonce_point, proof2.nonce_point);
+        assert_ne!(sig1, sig2);
+    }
+
+    #[test]
+    fn test_vectors() {
+        let msg = mpc::Commitment::from([8u8; 32]);
+        let vectors = [
+            (
+                None,
+                "d21a3bc0f014f8137b8d69b8b9d7b1efc5a5e8509a9cea55dce267d5f0509d7c",
+                "0343ac8f01b1d3fca0584992de69da1942ef0420568b36f9284dd7c1136b1df1b6",
+                "7d529a37386a78de955b30d4dda544e9570185e1112a8cc235116befc37fafa6\
+                 b6b8cdcfce315da1122689b79b0dc9871815112978ed6c498c4a39ee8aafccce",
+            ),
+            (
+                Some(script_root()),
+                "d3e89834b8c1e4e6e250b5dab6c183030a517205d61cbb4dc252986f8e3dc21d",
+                "020fcde04735bc975c357521739d1bba211be6858166a64669287fa238b96ed8bf",
+                "2067f544213e0c156591c408ac637337302fc672b98968d01da3dd388edceb77\
+                 a3e63cd2bfc7670698654dd128b35b8812bee76080a39f6510d509d88f738515",
+            ),
+        ];
+        assert_eq!(
+            script_root().to_hex(),
+            "a85b2107f791b26a84e7586c28cec7cb61202ed3d01944d832500f363782d675"
+        );
+        let internal_pk = InternalPk::from(keypair().x_only_public_key().0);
+        for (merkle_root, output_pk, nonce_point, sig) in vectors {
+            let (real_output_pk, _) = internal_pk.to_output_pk_with_root(merkle_root);
+            assert_eq!(real_output_pk.to_hex(), output_pk);
+            let (real_sig, proof) =
+                sign_taproot_key_spend(&keypair(), merkle_root, [0x5A; 32], &msg, [0u8; 32])
+                    .unwrap();
+            assert_eq!(proof.nonce_point.to_byte_array().to_hex(), nonce_point);
+            assert_eq!(real_sig.serialize().to_hex(), sig);
+        }
+    }
+
+    #[test]
+    fn reduce_modulo_order() {
+        assert_eq!(reduce([0x11; 32]).to_be_bytes(), [0x11; 32]);
+        assert_eq!(reduce(CURVE_ORDER).to_be_bytes(), [0u8; 32]);
+        assert_eq!(
+            reduce([0xFF; 32]).to_be_bytes(),
+            <[u8; 32]>::from_hex("000000000000000000000000000000014551231950b75fc4402da1732fc9bebe")
+                .unwrap()
+        );
+        let mut order_plus_five = CURVE_ORDER;
+        order_plus_five[31] += 5;
+        let mut five = [0u8; 32];
+        five[31] = 5;
+        assert_eq!(reduce(order_plus_five).to_be_bytes(), five);
+    }
+
+    #[test]
+    fn proof_encoding() {
+        let (_, proof) = sign_taproot_key_spend(
+            &keypair(),
+            None,
+            [0x5A; 32],
+            &mpc::Commitment::from([8u8; 32]),
+            [0u8; 32],
+        )
+        .unwrap();
+        let data = proof.to_strict_serialized::<33>().unwrap();
+        assert_eq!(data.as_slice(), proof.nonce_point.to_byte_array().as_slice());
+        assert_eq!(SigtweakProof::from_strict_serialized::<33>(data).unwrap(), proof);
+    }
+}
@@ -65,18 +65,26 @@ mod tapscript;
 mod tx;
 mod txout;
 mod spk;
+mod taptree;
 mod xonlypk;
 
-use bc::{InternalPk, IntoTapHash, LeafScript, ScriptPubkey, TapBranchHash, TapNodeHash, Tx};
+use bc::{
+    InternalPk, IntoTapHash, LeafScript, OutputPk, ScriptPubkey, TapBranchHash, TapNodeHash,
+    TapScript, TapTree, TaprootContext, Tx,
+};
 use commit_verify::mpc::Commitment;
-use commit_verify::{CommitmentProtocol, ConvolveCommitProof, ConvolveVerifyError};
+use commit_verify::{
+    CommitVerify, CommitmentProtocol, ConvolveCommit, ConvolveCommitProof, ConvolveVerifyError,
+};
 use strict_encoding::{StrictDeserialize, StrictSerialize};
+pub use spk::p2tr_tapret;
 pub use tapscript::{TapretCommitment, TAPRET_SCRIPT_COMMITMENT_PREFIX};
-pub use tx::TapretError;
-pub use xonlypk::TapretKeyError;
+pub use taptree::{tapret_tree, TapretTreeError};
+pub use tx::{tapret_commit_bounded, TapretError};
+pub use xonlypk::{is_tapret_output, TapretKeyError};
 
 use crate::proof::Method;
-use crate::{Proof, LIB_NAME_BPCORE};
+use crate::{Proof, LIB_NAME_BPCORE, MAX_TX_OUTPUTS};
 
 /// Marker non-instantiable enum defining LNPBP-12 taproot OP_RETURN (`tapret`)
 /// protocol.
@@ -212,13 +220,8 @@ impl TapretNodePartner {
     pub fn check_no_commitment(&self) -> bool {
         match self {
             TapretNodePartner::LeftNode(_) => true,
-            TapretNodePartner::RightLeaf(LeafScript { script, .. }) if script.len() < 64 => true,
-            TapretNodePartner::RightLeaf(LeafScript { script, .. }) => {
-                script[..31] != TAPRET_SCRIPT_COMMITMENT_PREFIX[..]
-            }
-            TapretNodePartner::RightBranch(right_branch) => {
-                right_branch.left_node_hash()[..31] != TAPRET_SCRIPT_COMMITMENT_PREFIX[..]
-            }
+            TapretNodePartner::RightLeaf(leaf_script) => !is_tapret_script(leaf_script),
+            TapretNodePartner::RightBranch(right_branch) => !is_tapret_branch(right_branch),
         }
     }
 
@@ -298,6 +301,31 @@ impl TapretPathProof {
         })
     }
 
+    /// Constructs path proof placing the tapret commitment to the `msg` at the
+    /// right side of the original script tree with the merkle `root`, picking
+    /// the first nonce which gives the correct ordering.
+    ///
+    /// # Errors
+    ///
+    /// If none of the nonce values orders the commitment leaf after the
+    /// original tree root.
+    pub fn with_left_root(
+        root: TapNodeHash,
+        msg: &Commitment,
+    ) -> Result<TapretPathProof, TapretKeyError> {
+        let partner = TapretNodePartner::LeftNode(root);
+        let mut nonce = 0u8;
+        loop {
+            let path_proof = TapretPathProof::with(partner.clone(), nonce)
+                .expect("left node never contains a commitment");
+            match path_proof.commitment_merkle_root(msg) {
+                Ok(_) => return Ok(path_proof),
+                Err(err) if nonce == u8::MAX => return Err(err),
+                Err(_) => nonce += 1,
+            }
+        }
+    }
+
     /// Checks that the sibling data does not contain another tapret commitment
     /// for any step of the mekrle path.
     #[inline]
@@ -317,6 +345,43 @@ impl TapretPathProof {
             .as_ref()
             .map(|partner| partner.tap_node_hash())
     }
+
+    /// Reconstructs merkle root of the tree after the tapret commitment to the
+    /// `msg` was inserted into it.
+    ///
+    /// Checks that the commitment leaf is inserted according to the LNPBP-12
+    /// rules: it must be either the only leaf of the tree, or a child of the
+    /// tree root, while the node partner must not contain an alternative
+    /// commitment. The ordering of the commitment relative to the partner
+    /// depends on the kind of the partner (see
+    /// [`TapretNodePartner::check_ordering`]):
+    /// - for [`TapretNodePartner::LeftNode`] the commitment must be on the
+    ///   right side, i.e. its hash must be not less than the partner hash;
+    /// - for [`TapretNodePartner::RightLeaf`] and
+    ///   [`TapretNodePartner::RightBranch`] the partner is revealed on the
+    ///   right side, so the commitment hash must be not greater than the
+    ///   partner hash.
+    ///
+    /// # Errors
+    ///
+    /// If the node partner contains alternative commitment, or the commitment
+    /// leaf hash is ordered on the wrong side of the partner.
+    pub fn commitment_merkle_root(&self, msg: &Commitment) -> Result<TapNodeHash, TapretKeyError> {
+        let tapret_commitment = TapretCommitment::with(*msg, self.nonce);
+        let commitment_leaf = TapScript::commit(&tapret_commitment).tap_leaf_hash();
+        let commitment_hash = commitment_leaf.into_tap_hash();
+
+        let Some(ref partner) = self.partner_node else {
+            return Ok(commitment_hash);
+        };
+        if !partner.check_no_commitment() {
+            return Err(TapretKeyError::AlternativeCommitment(partner.clone()));
+        }
+        if !partner.check_ordering(commitment_hash) {
+            return Err(TapretKeyError::IncorrectOrdering(partner.clone(), commitment_leaf));
+        }
+        Ok(TapBranchHash::with_nodes(commitment_hash, partner.tap_node_hash()).into_tap_hash())
+    }
 }
 
 /*
@@ -378,13 +443,487 @@ impl TapretProof {
         let merkle_root = self.path_proof.original_merkle_root();
         ScriptPubkey::p2tr(self.internal_pk, merkle_root)
     }
+
+    /// Reconstructs output key containing tapret commitment to the `msg`,
+    /// checking that the commitment is inserted into the script tree according
+    /// to the LNPBP-12 rules (see [`TapretPathProof::commitment_merkle_root`]).
+    pub fn committed_output_pk(&self, msg: &Commitment) -> Result<OutputPk, TapretKeyError> {
+        let merkle_root = self.path_proof.commitment_merkle_root(msg)?;
+        let (output_pk, _) = self.internal_pk.to_output_pk(Some(merkle_root));
+        Ok(output_pk)
+    }
+
+    /// Version of [`Self::committed_output_pk`] using the provided shared
+    /// secp256k1 context.
+    pub fn committed_output_pk_with(
+        &self,
+        ctx: &TaprootContext,
+        msg: &Commitment,
+    ) -> Result<OutputPk, TapretKeyError> {
+        let merkle_root = self.path_proof.commitment_merkle_root(msg)?;
+        let (output_pk, _) = ctx.to_output_pk(self.internal_pk, Some(merkle_root));
+        Ok(output_pk)
+    }
+
+    /// Verifies that the first taproot output of the transaction contains
+    /// tapret commitment to the `msg`, using the provided shared secp256k1
+    /// context.
+    ///
+    /// Unlike [`Proof::verify`], doesn't restore the original transaction and
+    /// checks only the first taproot output, which is the only output changed
+    /// by the tapret commitment.
+    ///
+    /// # Errors
+    ///
+    /// With [`TapretError::TooManyOutputs`] if the transaction has more than
+    /// [`MAX_TX_OUTPUTS`] outputs; use [`Self::verify_with_bounded`] to apply a
+    /// stricter limit.
+    #[inline]
+    pub fn verify_with(
+        &self,
+        ctx: &TaprootContext,
+        msg: &Commitment,
+        tx: &Tx,
+    ) -> Result<bool, TapretError> {
+        self.verify_with_bounded(ctx, msg, tx, MAX_TX_OUTPUTS)
+    }
+
+    /// Version of [`Self::verify_with`] rejecting transactions with more than
+    /// `max_outputs` outputs before scanning them.
+    ///
+    /// # Errors
+    ///
+    /// With [`TapretError::TooManyOutputs`] if the transaction has more than
+    /// `max_outputs` outputs.
+    pub fn verify_with_bounded(
+        &self,
+        ctx: &TaprootContext,
+        msg: &Commitment,
+        tx: &Tx,
+        max_outputs: usize,
+    ) -> Result<bool, TapretError> {
+        if tx.outputs.len() > max_outputs {
+            return Err(TapretError::TooManyOutputs(tx.outputs.len()));
+        }
+        let Some(txout) = tx.outputs().find(|txout| txout.script_pubkey.is_p2tr()) else {
+            return Ok(false);
+        };
+        Ok(self
+            .committed_output_pk_with(ctx, msg)
+            .map(|output_pk| txout.script_pubkey == ScriptPubkey::p2tr_tweaked(output_pk))
+            .unwrap_or_default())
+    }
+}
+
+impl TapretProof {
+    /// Constructs proof that a taproot output with the given internal key and
+    /// script tree does not contain a tapret commitment.
+    ///
+    /// Returns `None` if the script tree contains a tapret commitment at one
+    /// of the positions allowed for it by the tapret protocol (the tree root
+    /// or one of its children).
+    pub fn prove_no_commitment(
+        internal_pk: InternalPk,
+        script_tree: Option<&TapTree>,
+    ) -> Option<NoCommitmentProof> {
+        let script_root = script_tree.map(|tree| match tree.split() {
+            None => NoCommitmentRoot::Leaf(first_leaf_script(tree)),
+            Some((left, right)) => NoCommitmentRoot::Branch(
+                NoCommitmentNode::with_subtree(&left),
+                NoCommitmentNode::with_subtree(&right),
+            ),
+        });
+        let proof = NoCommitmentProof {
+            internal_pk,
+            script_root,
+        };
+        proof.check_no_commitment().then_some(proof)
+    }
+}
+
+fn first_leaf_script(tree: &TapTree) -> LeafScript {
+    tree.leaves()
+        .next()
+        .expect("taproot script tree always has at least one leaf")
+        .script
+        .clone()
+}
+
+fn is_tapret_script(leaf_script: &LeafScript) -> bool {
+    leaf_script.script.len() >= 64 &&
+        leaf_script.script[..31] == TAPRET_SCRIPT_COMMITMENT_PREFIX[..]
+}
+
+fn is_tapret_branch(branch: &TapretRightBranch) -> bool {
+    branch.left_node_hash()[..31] == TAPRET_SCRIPT_COMMITMENT_PREFIX[..]
+}
+
+/// Child of the taproot script tree root revealed by [`NoCommitmentProof`].
+#[derive(Clone, Ord, PartialOrd, Eq, PartialEq, Hash, Debug, Display)]
+#[derive(StrictType, StrictDumb, StrictEncode, StrictDecode)]
+#[strict_type(lib = LIB_NAME_BPCORE, tags = order, dumb = Self::Leaf(default!()))]
+#[cfg_attr(
+    feature = "serde",
+    derive(Serialize, Deserialize),
+    serde(crate = "serde_crate", rename_all = "camelCase")
+)]
+#[display(inner)]
+pub enum NoCommitmentNode {
+    /// Leaf script, which must not be a tapret commitment.
+    Leaf(LeafScript),
+
+    /// Branch, which can't be a tapret commitment leaf and thus is revealed
+    /// only by the hashes of its children.
+    Branch(TapretRightBranch),
+}
+
+impl NoCommitmentNode {
+    fn with_subtree(tree: &TapTree) -> Self {
+        match tree.split() {
+            None => NoCommitmentNode::Leaf(first_leaf_script(tree)),
+            Some((left, right)) => NoCommitmentNode::Branch(TapretRightBranch::with(
+                left.merkle_root(),
+                right.merkle_root(),
+            )),
+        }
+    }
+
+    /// Checks that the node is not a tapret commitment leaf. For a branch
+    /// this applies the same rule as [`TapretNodePartner::check_no_commitment`]:
+    /// the hash of its first child must not start with the tapret commitment
+    /// prefix.
+    pub fn check_no_commitment(&self) -> bool {
+        match self {
+            NoCommitmentNode::Leaf(leaf_script) => !is_tapret_script(leaf_script),
+            NoCommitmentNode::Branch(branch) => !is_tapret_branch(branch),
+        }
+    }
+
+    /// Computes node hash.
+    pub fn tap_node_hash(&self) -> TapNodeHash {
+        match self {
+            NoCommitmentNode::Leaf(leaf_script) => leaf_script.tap_leaf_hash().into_tap_hash(),
+            NoCommitmentNode::Branch(branch) => branch.node_hash(),
+        }
+    }
+}
+
+/// Root of the taproot script tree revealed by [`NoCommitmentProof`].
+#[derive(Clone, Ord, PartialOrd, Eq, PartialEq, Hash, Debug)]
+#[derive(StrictType, StrictDumb, StrictEncode, StrictDecode)]
+#[strict_type(lib = LIB_NAME_BPCORE, tags = order, dumb = Self::Leaf(default!()))]
+#[cfg_attr(
+    feature = "serde",
+    derive(Serialize, Deserialize),
+    serde(crate = "serde_crate", rename_all = "camelCase")
+)]
+pub enum NoCommitmentRoot {
+    /// The tree consists of a single leaf script, which must not be a tapret
+    /// commitment.
+    Leaf(LeafScript),
+
+    /// The tree root is a branch; none of its children may be a tapret
+    /// commitment leaf.
+    Branch(NoCommitmentNode, NoCommitmentNode),
+}
+
+impl NoCommitmentRoot {
+    /// Checks that neither the root nor its children are tapret commitment
+    /// leafs.
+    pub fn check_no_commitment(&self) -> bool {
+        match self {
+            NoCommitmentRoot::Leaf(leaf_script) => !is_tapret_script(leaf_script),
+            NoCommitmentRoot::Branch(left, right) => {
+                left.check_no_commitment() && right.check_no_commitment()
+            }
+        }
+    }
+
+    /// Computes merkle root of the script tree.
+    pub fn tap_node_hash(&self) -> TapNodeHash {
+        match self {
+            NoCommitmentRoot::Leaf(leaf_script) => leaf_script.tap_leaf_hash().into_tap_hash(),
+            NoCommitmentRoot::Branch(left, right) => {
+                TapBranchHash::with_nodes(left.tap_node_hash(), right.tap_node_hash())
+                    .into_tap_hash()
+            }
+        }
+    }
+}
+
+/// Proof that a taproot output does not contain a tapret commitment.
+///
+/// Tapret commitment may be present only as the root of the taproot script
+/// tree, or as one of the root children. The proof reveals the internal key and
+/// the first level of the script tree, demonstrating that the output key is
+/// derived from the original script tree with no tapret commitment leaf
+/// inserted.
+#[derive(Clone, Ord, PartialOrd, Eq, PartialEq, Hash, Debug)]
+#[derive(StrictType, StrictDumb, StrictEncode, StrictDecode)]
+#[strict_type(lib = LIB_NAME_BPCORE)]
+#[cfg_attr(
+    feature = "serde",
+    derive(Serialize, Deserialize),
+    serde(crate = "serde_crate", rename_all = "camelCase")
+)]
+pub struct NoCommitmentProof {
+    /// The internal key used by the taproot output.
+    pub internal_pk: InternalPk,
+
+    /// Revealed first level of the taproot script tree, if the output has
+    /// script path spendings.
+    pub script_root: Option<NoCommitmentRoot>,
+}
+
+impl NoCommitmentProof {
+    /// Returns merkle root of the script tree, if present.
+    #[inline]
+    pub fn merkle_root(&self) -> Option<TapNodeHash> {
+        self.script_root.as_ref().map(NoCommitmentRoot::tap_node_hash)
+    }
+
+    /// Checks that the revealed part of the script tree doesn't contain tapret
+    /// commitment.
+    #[inline]
+    pub fn check_no_commitment(&self) -> bool {
+        self.script_root
+            .as_ref()
+            .map(NoCommitmentRoot::check_no_commitment)
+            .unwrap_or(true)
+    }
+
+    /// Verifies that the output key doesn't contain tapret commitment.
+    pub fn verify(&self, output_pk: OutputPk) -> bool {
+        let (expected, _) = self.internal_pk.to_output_pk(self.merkle_root());
+        self.check_no_commitment() && expected == output_pk
+    }
 }
 
 impl Proof<Method> for TapretProof {
     type Error = ConvolveVerifyError;
-    const METHOD: Method = Method::TapretFirst;
+
+    fn method(&self) -> Method { Method::TapretFirst }
 
     fn verify(&self, msg: &Commitment, tx: &Tx) -> Result<(), ConvolveVerifyError> {
         ConvolveCommitProof::<_, Tx, _>::verify(self, msg, tx)
     }
+
+    fn commitment_value(&self, msg: &Commitment) -> Option<[u8; 32]> {
+        let (output_key, _) = self.internal_pk.convolve_commit(&self.path_proof, msg).ok()?;
+        Some(output_key.to_byte_array())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use bc::{LeafInfo, TapScript};
+    use commit_verify::{mpc, CommitVerify};
+
+    use super::*;
+    use crate::test_helpers::internal_pk;
+
+    fn leaf(op_code: u8) -> LeafScript {
+        LeafScript::from_tap_script(TapScript::from_unsafe(vec![op_code]))
+    }
+
+    fn commitment_leaf() -> LeafScript {
+        let msg = mpc::Commitment::from([8u8; 32]);
+        LeafScript::from_tap_script(TapScript::commit(&TapretCommitment::with(msg, 0)))
+    }
+
+    #[test]
+    fn commitment_insertion() {
+        let internal_pk = internal_pk();
+        let msg = mpc::Commitment::from([8u8; 32]);
+        let commitment_hash = |nonce| {
+            TapScript::commit(&TapretCommitment::with(msg, nonce))
+                .tap_leaf_hash()
+                .into_tap_hash()
+        };
+
+        // Commitment as the only leaf of the tree
+        let path_proof = TapretPathProof::root(0);
+        assert_eq!(path_proof.commitment_merkle_root(&msg), Ok(commitment_hash(0)));
+        let proof = TapretProof {
+            path_proof: path_proof.clone(),
+            internal_pk,
+        };
+        let (output_pk, _) = internal_pk.convolve_commit(&path_proof, &msg).unwrap();
+        assert_eq!(proof.committed_output_pk(&msg), Ok(output_pk));
+        assert_ne!(proof.committed_output_pk(&mpc::Commitment::from([9u8; 32])), Ok(output_pk));
+
+        // Right-side partner must have hash not less than the commitment
+        let partner = TapretNodePartner::RightLeaf(leaf(0x51));
+        let right_node = partner.tap_node_hash();
+        let (valid, invalid): (Vec<u8>, Vec<u8>) =
+            (0..=u8::MAX).partition(|nonce| commitment_hash(*nonce) <= right_node);
+        assert!(!valid.is_empty() && !invalid.is_empty());
+
+        let path_proof = TapretPathProof::with(partner.clone(), valid[0]).unwrap();
+        let root = path_proof.commitment_merkle_root(&msg).unwrap();
+        let expected = TapBranchHash::with_nodes(commitment_hash(valid[0]), right_node);
+        assert_eq!(root, expected.into_tap_hash());
+        let proof = TapretProof {
+            path_proof: path_proof.clone(),
+            internal_pk,
+        };
+        let (output_pk, _) = internal_pk.convolve_commit(&path_proof, &msg).unwrap();
+        assert_eq!(proof.committed_output_pk(&msg), Ok(output_pk));
+
+        let path_proof = TapretPathProof::with(partner.clone(), invalid[0]).unwrap();
+        assert!(matches!(
+            path_proof.commitment_merkle_root(&msg),
+            Err(TapretKeyError::IncorrectOrdering(p, _)) if p == partner
+        ));
+
+        // Left-side partner must have hash not greater than the commitment
+        let partner = TapretNodePartner::LeftNode(TapNodeHash::from([0xFF; 32]));
+        let path_proof = TapretPathProof::with(partner, 0).unwrap();
+        assert!(matches!(
+            path_proof.commitment_merkle_root(&msg),
+            Err(TapretKeyError::IncorrectOrdering(..))
+        ));
+        let partner = TapretNodePartner::LeftNode(TapNodeHash::from([0x00; 32]));
+        let path_proof = TapretPathProof::with(partner, 0).unwrap();
+        assert!(path_proof.commitment_merkle_root(&msg).is_ok());
+    }
+
+    #[test]
+    fn with_left_root() {
+        let msg = mpc::Commitment::from([8u8; 32]);
+        let root = TapNodeHash::from([0x80; 32]);
+        let path_proof = TapretPathProof::with_left_root(root, &msg).unwrap();
+        assert_eq!(path_proof.original_merkle_root(), Some(root));
+        assert!(path_proof.commitment_merkle_root(&msg).is_ok());
+        for nonce in 0..path_proof.nonce() {
+            let partner = TapretNodePartner::LeftNode(root);
+            let other = TapretPathProof::with(partner, nonce).unwrap();
+            assert!(other.commitment_merkle_root(&msg).is_err());
+        }
+
+        let root = TapNodeHash::from([0xFF; 32]);
+        assert!(matches!(
+            TapretPathProof::with_left_root(root, &msg),
+            Err(TapretKeyError::IncorrectOrdering(..))
+        ));
+    }
+
+    #[test]
+    fn short_tapret_scripts() {
+        let script = commitment_leaf().script.into_vec();
+        for len in [0, 1, 2, 30, 31, 32, 63] {
+            let leaf_script =
+                LeafScript::from_tap_script(TapScript::from_unsafe(script[..len].to_vec()));
+            assert!(!is_tapret_script(&leaf_script), "script of {len} bytes");
+            assert!(TapretNodePartner::RightLeaf(leaf_script).check_no_commitment());
+        }
+        assert!(is_tapret_script(&commitment_leaf()));
+        assert!(!TapretNodePartner::RightLeaf(commitment_leaf()).check_no_commitment());
+    }
+
+    #[test]
+    fn prefixed_branch() {
+        let mut prefixed = [0u8; 32];
+        prefixed[..31].copy_from_slice(&TAPRET_SCRIPT_COMMITMENT_PREFIX);
+        let prefixed = TapNodeHash::from(prefixed);
+        let other = TapNodeHash::from([0xFF; 32]);
+        let branch = TapretRightBranch::with(prefixed, other);
+        assert!(is_tapret_branch(&branch));
+        assert!(!TapretNodePartner::RightBranch(branch).check_no_commitment());
+        assert!(!NoCommitmentNode::Branch(branch).check_no_commitment());
+
+        let branch = TapretRightBranch::with(TapNodeHash::from([0x00; 32]), other);
+        assert!(!is_tapret_branch(&branch));
+        assert!(TapretNodePartner::RightBranch(branch).check_no_commitment());
+        assert!(NoCommitmentNode::Branch(branch).check_no_commitment());
+    }
+
+    #[test]
+    fn no_commitment_key_only() {
+        let internal_pk = internal_pk();
+        let (output_pk, _) = internal_pk.to_output_pk_with_root(None);
+        let proof = TapretProof::prove_no_commitment(internal_pk, None).unwrap();
+        assert!(proof.verify(output_pk));
+    }
+
+    #[test]
+    fn no_commitment_script_tree() {
+        let internal_pk = internal_pk();
+        for count in 1..=5u8 {
+            let tree = TapTree::balanced((0..count).map(|no| leaf(0x51 + no))).unwrap();
+            let (output_pk, _) = internal_pk.to_output_pk(Some(tree.merkle_root()));
+            let proof = TapretProof::prove_no_commitment(internal_pk, Some(&tree)).unwrap();
+            assert_eq!(proof.merkle_root(), Some(tree.merkle_root()));
+            assert!(proof.verify(output_pk));
+        }
+    }
+
+    #[test]
+    fn commitment_present() {
+        let internal_pk = internal_pk();
+        let msg = mpc::Commitment::from([8u8; 32]);
+        let (output_pk, _) = internal_pk
+            .convolve_commit(&TapretPathProof::root(0), &msg)
+            .unwrap();
+
+        // Claiming that the output has no script tree fails
+        let proof = TapretProof::prove_no_commitment(internal_pk, None).unwrap();
+        assert!(!proof.verify(output_pk));
+
+        // Commitment can't be proven absent, neither at the root nor at the
+        // first level of the tree
+        let tree = TapTree::balanced([commitment_leaf()]).unwrap();
+        assert_eq!(TapretProof::prove_no_commitment(internal_pk, Some(&tree)), None);
+        let tree = TapTree::balanced([leaf(0x51), commitment_leaf()]).unwrap();
+        assert_eq!(TapretProof::prove_no_commitment(internal_pk, Some(&tree)), None);
+
+        // Forged proof revealing commitment as the tree root
+        let proof = NoCommitmentProof {
+            internal_pk,
+            script_root: Some(NoCommitmentRoot::Leaf(commitment_leaf())),
+        };
+        assert!(!proof.verify(output_pk));
+
+        // Commitment deeper in the tree is not a valid tapret commitment
+        let tree = TapTree::with_leaves([
+            LeafInfo::with(1, leaf(0x51)),
+            LeafInfo::with(2, leaf(0x52)),
+            LeafInfo::with(2, commitment_leaf()),
+        ])
+        .unwrap();
+        let (output_pk, _) = internal_pk.to_output_pk(Some(tree.merkle_root()));
+        let proof = TapretProof::prove_no_commitment(internal_pk, Some(&tree)).unwrap();
+        assert!(proof.verify(output_pk));
+    }
+
+    #[test]
+    fn verify_with_context() {
+        use bc::{LockTime, TxOut, TxVer, VarIntArray};
+
+        let internal_pk = internal_pk();
+        let msg = mpc::Commitment::from([8u8; 32]);
+        let tx = |outputs: Vec<TxOut>| Tx {
+            version: TxVer::V2,
+            inputs: none!(),
+            outputs: VarIntArray::try_from(outputs).unwrap(),
+            lock_time: LockTime::ZERO,
+        };
+        let p2wpkh = TxOut::new(ScriptPubkey::p2wpkh([0xA1; 20]), 1000u64);
+        let p2tr = TxOut::new(ScriptPubkey::p2tr_key_only(internal_pk), 1000u64);
+        let original = tx(vec![p2wpkh.clone(), p2tr]);
+        let supplement = TapretProof {
+            path_proof: TapretPathProof::root(0),
+            internal_pk,
+        };
+        let (committed, proof) = original.convolve_commit(&supplement, &msg).unwrap();
+
+        let ctx = TaprootContext::randomized(&[0xA5; 32]);
+        assert_eq!(proof.committed_output_pk_with(&ctx, &msg), proof.committed_output_pk(&msg));
+        assert_eq!(proof.verify_with(&ctx, &msg, &committed), Ok(true));
+        assert!(Proof::verify(&proof, &msg, &committed).is_ok());
+        let other_msg = mpc::Commitment::from([9u8; 32]);
+        assert_eq!(proof.verify_with(&ctx, &other_msg, &committed), Ok(false));
+        assert_eq!(proof.verify_with(&ctx, &msg, &original), Ok(false));
+        assert_eq!(proof.verify_with(&ctx, &msg, &tx(vec![p2wpkh])), Ok(false));
+    }
 }
@@ -19,10 +19,43 @@
 // See the License for the specific language governing permissions and
 // limitations under the License.
 
-use bc::ScriptPubkey;
+use bc::{InternalPk, IntoTapHash, ScriptPubkey};
 use commit_verify::{mpc, ConvolveCommit, ConvolveCommitProof};
 
-use super::{TapretFirst, TapretKeyError, TapretProof};
+use super::{TapretFirst, TapretKeyError, TapretPathProof, TapretProof};
+
+/// Constructs taproot `scriptPubkey` committing to the `msg` with a tapret
+/// commitment, returning it together with the proof of the commitment.
+///
+/// If there is no `original_merkle_root` the commitment becomes the only leaf
+/// of the script tree; otherwise it is added next to the original tree root,
+/// picking the first nonce which places the commitment at the right side of
+/// the tree.
+///
+/// # Errors
+///
+/// If none of the nonce values orders the commitment leaf after the original
+/// tree root.
+pub fn p2tr_tapret(
+    internal_pk: InternalPk,
+    original_merkle_root: Option<impl IntoTapHash>,
+    msg: &mpc::Commitment,
+) -> Result<(ScriptPubkey, TapretProof), TapretKeyError> {
+    let Some(root) = original_merkle_root.map(IntoTapHash::into_tap_hash) else {
+        return commit(internal_pk, TapretPathProof::root(0), msg);
+    };
+    let path_proof = TapretPathProof::with_left_root(root, msg)?;
+    commit(internal_pk, path_proof, msg)
+}
+
+fn commit(
+    internal_pk: InternalPk,
+    path_proof: TapretPathProof,
+    msg: &mpc::Commitment,
+) -> Result<(ScriptPubkey, TapretProof), TapretKeyError> {
+    let (output_pk, proof) = internal_pk.convolve_commit(&path_proof, msg)?;
+    Ok((ScriptPubkey::p2tr_tweaked(output_pk), proof))
+}
 
 impl ConvolveCommitProof<mpc::Commitment, ScriptPubkey, TapretFirst> for TapretProof {
     type Suppl = Self;
@@ -50,3 +83,54 @@ impl ConvolveCommit<mpc::Commitment, TapretProof, TapretFirst> for ScriptPubkey
         Ok((script_pubkey, supplement.clone()))
     }
 }
+
+#[cfg(test)]
+mod test {
+    use bc::{LeafScript, TapNodeHash, TapScript, TapTree};
+    use commit_verify::mpc::Commitment;
+
+    use super::*;
+    use crate::test_helpers::internal_pk;
+
+    #[test]
+    fn p2tr_tapret_key_only() {
+        let internal_pk = internal_pk();
+        let msg = Commitment::from([8u8; 32]);
+
+        let (script_pubkey, proof) = p2tr_tapret(internal_pk, None::<TapNodeHash>, &msg).unwrap();
+        assert_eq!(proof.path_proof, TapretPathProof::root(0));
+        assert_eq!(proof.original_pubkey_script(), ScriptPubkey::p2tr_key_only(internal_pk));
+        assert_eq!(
+            proof.original_pubkey_script().convolve_commit(&proof, &msg),
+            Ok((script_pubkey.clone(), proof.clone()))
+        );
+        ConvolveCommitProof::<_, ScriptPubkey, _>::verify(&proof, &msg, &script_pubkey).unwrap();
+    }
+
+    #[test]
+    fn p2tr_tapret_script_tree() {
+        let internal_pk = internal_pk();
+        let msg = Commitment::from([8u8; 32]);
+        let tree = TapTree::balanced([
+            LeafScript::from_tap_script(TapScript::from_unsafe(vec![0x51])),
+            LeafScript::from_tap_script(TapScript::from_unsafe(vec![0x52])),
+        ])
+        .unwrap();
+        let root = tree.merkle_root();
+
+        let (script_pubkey, proof) = p2tr_tapret(internal_pk, Some(root), &msg).unwrap();
+        assert_eq!(proof.path_proof.original_merkle_root(), Some(root));
+        assert_eq!(proof.original_pubkey_script(), ScriptPubkey::p2tr(internal_pk, Some(root)));
+        assert_eq!(
+            proof.original_pubkey_script().convolve_commit(&proof, &msg),
+            Ok((script_pubkey.clone(), proof.clone()))
+        );
+        ConvolveCommitProof::<_, ScriptPubkey, _>::verify(&proof, &msg, &script_pubkey).unwrap();
+        assert!(ConvolveCommitProof::<_, ScriptPubkey, _>::verify(
+            &proof,
+            &Commitment::from([9u8; 32]),
+            &script_pubkey
+        )
+        .is_err());
+    }
+}
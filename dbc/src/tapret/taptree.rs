@@ -0,0 +1,145 @@
+// Deterministic bitcoin commitments library.
+//
+// SPDX-License-Identifier: Apache-2.0
+//
+// Written in 2019-2024 by
+//     Dr Maxim Orlovsky <orlovsky@lnp-bp.org>
+//
+// Copyright (C) 2019-2024 LNP/BP Standards Association. All rights reserved.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use bc::{InternalPk, LeafInfo, LeafScript, TapScript, TapTree, TapTreeError};
+use commit_verify::{mpc, CommitVerify};
+
+use super::{
+    first_leaf_script, is_tapret_script, TapretCommitment, TapretKeyError, TapretNodePartner,
+    TapretPathProof, TapretProof,
+};
+
+/// Errors embedding tapret commitment into an existing taproot script tree.
+#[derive(Clone, Eq, PartialEq, Debug, Display, Error, From)]
+pub enum TapretTreeError {
+    /// Error constructing tapret commitment.
+    #[from]
+    #[display(inner)]
+    Key(TapretKeyError),
+
+    /// Error constructing script tree with the commitment.
+    #[from]
+    #[display(inner)]
+    Tree(TapTreeError),
+}
+
+/// Adds tapret commitment to the `msg` into an existing taproot script tree,
+/// returning the new script tree together with the proof of the commitment
+/// made with the `internal_pk`.
+///
+/// The commitment leaf becomes a child of the new tree root, and the original
+/// tree becomes its sibling, picking the first nonce which places the
+/// commitment at the right side of the tree. All original spending paths are
+/// preserved, however each of the original leaves moves one level deeper, thus
+/// the merkle paths inside their control blocks get an additional node (the
+/// commitment leaf hash) and callers MUST recompute the control blocks for the
+/// new tree.
+///
+/// # Errors
+///
+/// - if the original tree consists of a single tapret commitment leaf, which
+///   would become an alternative commitment;
+/// - if none of the nonce values orders the commitment leaf after the original
+///   tree root;
+/// - if the original tree already has the maximum depth.
+pub fn tapret_tree(
+    tree: &TapTree,
+    internal_pk: InternalPk,
+    msg: &mpc::Commitment,
+) -> Result<(TapTree, TapretProof), TapretTreeError> {
+    if tree.split().is_none() {
+        let leaf_script = first_leaf_script(tree);
+        if is_tapret_script(&leaf_script) {
+            let partner = TapretNodePartner::RightLeaf(leaf_script);
+            return Err(TapretKeyError::AlternativeCommitment(partner).into());
+        }
+    }
+
+    let path_proof = TapretPathProof::with_left_root(tree.merkle_root(), msg)?;
+    let commitment = TapScript::commit(&TapretCommitment::with(*msg, path_proof.nonce()));
+    let leaves = tree
+        .leaves()
+        .map(|leaf| LeafInfo::with(leaf.depth + 1, leaf.script.clone()))
+        .chain([LeafInfo::with(1, LeafScript::from_tap_script(commitment))]);
+    let tree = TapTree::with_leaves(leaves)?;
+    debug_assert_eq!(path_proof.commitment_merkle_root(msg), Ok(tree.merkle_root()));
+
+    Ok((tree, TapretProof {
+        path_proof,
+        internal_pk,
+    }))
+}
+
+#[cfg(test)]
+mod test {
+    use bc::{ControlBlock, LeafVer, ScriptPubkey, TapBranchHash, TapMerklePath};
+
+    use super::*;
+    use crate::tapret::p2tr_tapret;
+    use crate::test_helpers::internal_pk;
+
+    fn leaf(op_code: u8) -> LeafScript {
+        LeafScript::from_tap_script(TapScript::from_unsafe(vec![op_code]))
+    }
+
+    #[test]
+    fn two_leaf_tree() {
+        let internal_pk = internal_pk();
+        let msg = mpc::Commitment::from([8u8; 32]);
+        let original = TapTree::balanced([leaf(0x51), leaf(0x52)]).unwrap();
+
+        let (tree, proof) = tapret_tree(&original, internal_pk, &msg).unwrap();
+        assert_eq!(tree.leaves().map(|leaf| leaf.depth).collect::<Vec<_>>(), vec![2, 2, 1]);
+        assert_eq!(tree.leaves().next().unwrap().script, leaf(0x51));
+        assert_eq!(proof.path_proof.original_merkle_root(), Some(original.merkle_root()));
+        assert_eq!(proof.path_proof.commitment_merkle_root(&msg), Ok(tree.merkle_root()));
+
+        let (output_pk, parity) = internal_pk.to_output_pk(Some(tree.merkle_root()));
+        assert_eq!(proof.committed_output_pk(&msg), Ok(output_pk));
+        let (script_pubkey, _) =
+            p2tr_tapret(internal_pk, Some(original.merkle_root()), &msg).unwrap();
+        assert_eq!(script_pubkey, ScriptPubkey::p2tr_tweaked(output_pk));
+
+        // original spending path requires recomputed control block
+        let sibling = TapBranchHash::from(leaf(0x52).tap_leaf_hash().into_inner());
+        let commitment_leaf = &tree.leaves().last().unwrap().script;
+        let commitment = TapBranchHash::from(commitment_leaf.tap_leaf_hash().into_inner());
+        let path = TapMerklePath::try_from_iter([sibling]).unwrap();
+        let control_block = ControlBlock::with(LeafVer::TapScript, internal_pk, parity, path);
+        assert!(!control_block.verify(output_pk, &leaf(0x51)));
+        let path = TapMerklePath::try_from_iter([sibling, commitment]).unwrap();
+        let control_block = ControlBlock::with(LeafVer::TapScript, internal_pk, parity, path);
+        assert!(control_block.verify(output_pk, &leaf(0x51)));
+    }
+
+    #[test]
+    fn alternative_commitment() {
+        let commitment = TapretCommitment::with(mpc::Commitment::from([7u8; 32]), 0);
+        let leaf_script = LeafScript::from_tap_script(TapScript::commit(&commitment));
+        let original = TapTree::balanced([leaf_script.clone()]).unwrap();
+        assert_eq!(
+            tapret_tree(&original, internal_pk(), &mpc::Commitment::from([8u8; 32])),
+            Err(TapretTreeError::Key(TapretKeyError::AlternativeCommitment(
+                TapretNodePartner::RightLeaf(leaf_script)
+            )))
+        );
+    }
+}
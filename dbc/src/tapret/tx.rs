@@ -23,6 +23,7 @@ use bc::Tx;
 use commit_verify::{mpc, ConvolveCommit, ConvolveCommitProof};
 
 use super::{TapretFirst, TapretKeyError, TapretProof};
+use crate::MAX_TX_OUTPUTS;
 
 /// Errors during tapret commitment.
 #[derive(Clone, Eq, PartialEq, Debug, Display, Error, From)]
@@ -40,13 +41,66 @@ pub enum TapretError {
     /// tapret commitment in a transaction lacking any taproot outputs.
     #[display(doc_comments)]
     NoTaprootOutput,
+
+    /// transaction has too many outputs ({0}) to be scanned for the
+    /// commitment.
+    #[display(doc_comments)]
+    TooManyOutputs(usize),
 }
 
-impl ConvolveCommitProof<mpc::Commitment, Tx, TapretFirst> for TapretProof {
-    type Suppl = Self;
+/// Adds tapret commitment to the `msg` into the first taproot output of the
+/// transaction, rejecting transactions with more than `max_outputs` outputs
+/// before scanning them.
+///
+/// [`ConvolveCommit`] implementation for [`Tx`] uses this function with the
+/// [`MAX_TX_OUTPUTS`] limit.
+///
+/// # Errors
+///
+/// - [`TapretError::TooManyOutputs`] if the transaction has more than
+///   `max_outputs` outputs;
+/// - [`TapretError::NoTaprootOutput`] if the transaction has no taproot
+///   outputs;
+/// - [`TapretError::KeyEmbedding`] if the commitment can't be added to the
+///   output key.
+pub fn tapret_commit_bounded(
+    tx: &Tx,
+    supplement: &TapretProof,
+    msg: &mpc::Commitment,
+    max_outputs: usize,
+) -> Result<(Tx, TapretProof), TapretError> {
+    if tx.outputs.len() > max_outputs {
+        return Err(TapretError::TooManyOutputs(tx.outputs.len()));
+    }
+    let mut tx = tx.clone();
 
-    fn restore_original(&self, commitment: &Tx) -> Tx {
-        let mut tx = commitment.clone();
+    for txout in &mut tx.outputs {
+        if txout.script_pubkey.is_p2tr() {
+            let (commitment, proof) = txout
+                .convolve_commit(supplement, msg)
+                .map_err(TapretError::from)?;
+            *txout = commitment;
+            return Ok((tx, proof));
+        }
+    }
+
+    Err(TapretError::NoTaprootOutput)
+}
+
+impl TapretProof {
+    /// Restores the transaction as it was before the tapret commitment,
+    /// rejecting transactions with more than `max_outputs` outputs before
+    /// scanning them.
+    ///
+    /// # Errors
+    ///
+    /// With [`TapretError::TooManyOutputs`] if the transaction has more than
+    /// `max_outputs` outputs.
+    pub fn restore_original_bounded(&self, tx: &Tx, max_outputs: usize) -> Result<Tx, TapretError> {
+        if tx.outputs.len() > max_outputs {
+            return Err(TapretError::TooManyOutputs(tx.outputs.len()));
+        }
+        let mut tx = tx.clone();
 
         for txout in &mut tx.outputs {
             if txout.script_pubkey.is_p2tr() {
@@ -54,7 +108,20 @@ impl ConvolveCommitProof<mpc::Commitment, Tx, TapretFirst> for TapretProof {
                 break;
             }
         }
-        tx
+        Ok(tx)
+    }
+}
+
+impl ConvolveCommitProof<mpc::Commitment, Tx, TapretFirst> for TapretProof {
+    type Suppl = Self;
+
+    /// The trait doesn't allow to report errors, thus transactions with more
+    /// than [`MAX_TX_OUTPUTS`] outputs are returned unmodified, and the
+    /// verification fails since [`ConvolveCommit::convolve_commit`] rejects
+    /// them. Use [`TapretProof::restore_original_bounded`] to get the error.
+    fn restore_original(&self, commitment: &Tx) -> Tx {
+        self.restore_original_bounded(commitment, MAX_TX_OUTPUTS)
+            .unwrap_or_else(|_| commitment.clone())
     }
 
     fn extract_supplement(&self) -> &Self::Suppl { self }
@@ -64,24 +131,13 @@ impl ConvolveCommit<mpc::Commitment, TapretProof, TapretFirst> for Tx {
     type Commitment = Tx;
     type CommitError = TapretError;
 
+    #[inline]
     fn convolve_commit(
         &self,
         supplement: &TapretProof,
         msg: &mpc::Commitment,
     ) -> Result<(Tx, TapretProof), Self::CommitError> {
-        let mut tx = self.clone();
-
-        for txout in &mut tx.outputs {
-            if txout.script_pubkey.is_p2tr() {
-                let (commitment, proof) = txout
-                    .convolve_commit(supplement, msg)
-                    .map_err(TapretError::from)?;
-                *txout = commitment;
-                return Ok((tx, proof));
-            }
-        }
-
-        Err(TapretError::NoTaprootOutput)
+        tapret_commit_bounded(self, supplement, msg, MAX_TX_OUTPUTS)
     }
 }
 
@@ -91,13 +147,15 @@ mod test {
 
     use amplify::hex::FromHex;
     use amplify::Bytes32;
-    use bc::InternalPk;
+    use bc::{InternalPk, LockTime, ScriptPubkey, TaprootContext, TxOut, TxVer, VarIntArray};
     use commit_verify::mpc::Commitment;
     use commit_verify::ConvolveVerifyError;
     use secp256k1::{ffi, XOnlyPublicKey};
 
     use super::*;
     use crate::tapret::TapretPathProof;
+    use crate::test_helpers::internal_pk;
+    use crate::Proof;
 
     #[test]
     fn no_commitment() {
@@ -124,4 +182,103 @@ mod test {
             Err(ConvolveVerifyError::CommitmentMismatch)
         );
     }
+
+    #[test]
+    fn no_outputs() {
+        let tx = Tx {
+            version: TxVer::V2,
+            inputs: none!(),
+            outputs: none!(),
+            lock_time: LockTime::ZERO,
+        };
+        let internal_pk = internal_pk();
+        let proof = TapretProof {
+            path_proof: TapretPathProof::root(0),
+            internal_pk,
+        };
+        let msg = Commitment::from([8u8; 32]);
+
+        assert_eq!(tx.convolve_commit(&proof, &msg), Err(TapretError::NoTaprootOutput));
+        assert!(ConvolveCommitProof::<_, Tx, _>::verify(&proof, &msg, &tx).is_err());
+        assert!(Proof::verify(&proof, &msg, &tx).is_err());
+    }
+
+    #[test]
+    fn output_limit() {
+        let internal_pk = internal_pk();
+        let proof = TapretProof {
+            path_proof: TapretPathProof::root(0),
+            internal_pk,
+        };
+        let msg = Commitment::from([8u8; 32]);
+        let ctx = TaprootContext::default();
+        let p2tr = TxOut::new(proof.original_pubkey_script(), 0u64);
+        let outputs = vec![TxOut::new(ScriptPubkey::new(), 0u64), p2tr.clone()];
+        let mut tx = Tx {
+            version: TxVer::V2,
+            inputs: none!(),
+            outputs: VarIntArray::try_from(outputs).unwrap(),
+            lock_time: LockTime::ZERO,
+        };
+
+        let (mut committed, proof) = tapret_commit_bounded(&tx, &proof, &msg, 2).unwrap();
+        assert_eq!(tx.convolve_commit(&proof, &msg), Ok((committed.clone(), proof.clone())));
+        assert_eq!(proof.restore_original_bounded(&committed, 2), Ok(tx.clone()));
+        assert_eq!(proof.verify_with_bounded(&ctx, &msg, &committed, 2), Ok(true));
+        assert_eq!(
+            tapret_commit_bounded(&tx, &proof, &msg, 1),
+            Err(TapretError::TooManyOutputs(2))
+        );
+        assert_eq!(
+            proof.restore_original_bounded(&committed, 1),
+            Err(TapretError::TooManyOutputs(2))
+        );
+        assert_eq!(
+            proof.verify_with_bounded(&ctx, &msg, &committed, 1),
+            Err(TapretError::TooManyOutputs(2))
+        );
+
+        // default limit
+        let padding = vec![TxOut::new(ScriptPubkey::new(), 0u64); MAX_TX_OUTPUTS - 1];
+        for txout in padding {
+            tx.outputs.push(txout.clone()).unwrap();
+            committed.outputs.push(txout).unwrap();
+        }
+        let too_many = TapretError::TooManyOutputs(MAX_TX_OUTPUTS + 1);
+        assert_eq!(tx.convolve_commit(&proof, &msg), Err(too_many.clone()));
+        assert_eq!(
+            proof.restore_original_bounded(&committed, MAX_TX_OUTPUTS),
+            Err(too_many.clone())
+        );
+        assert_eq!(proof.verify_with(&ctx, &msg, &committed), Err(too_many));
+        assert_eq!(
+            ConvolveCommitProof::<_, Tx, _>::restore_original(&proof, &committed),
+            committed
+        );
+        assert!(ConvolveCommitProof::<_, Tx, _>::verify(&proof, &msg, &committed).is_err());
+    }
+
+    #[test]
+    fn commitment_value() {
+        let tx = Tx::from_str(
+            "020000000001027763e2a0ad25d45b63a19c33491b67c5037e72709121290bac5481a5d5d0c9330100000000ffffffff7763e2a0ad25d45b63a19c33491b67c5037e72709121290bac5481a5d5d0c9330400000000ffffffff02026e010000000000225120455dfcc062ef80609b007377f127e4abdb5cb0052158af1fab7aa628c34563f1d508000000000000225120a2788d4208ec6b4b600aef4c13075cf1d47bda0299ed1e6eedce4e7a90fb2a2c0141150df5377a34deded048dc01bff3d4f5f31d8a89fe2fbf1d0295993c1f899b3cefd1a63900ea6346b78edd476524c08ae094ff417bfa525b585ee66ebc26bb9e010141d959f21b498d90c2ff9f5b0bf3aee9158527501162eab2e3d56371714877a97df80caab15e366855aa56443b7d081c234a4ce4d6414815a874624cbe46b643370100000000"
+        ).unwrap();
+        let internal_pk = internal_pk();
+        let proof = TapretProof {
+            path_proof: TapretPathProof::root(0),
+            internal_pk,
+        };
+        let msg = Commitment::from([8u8; 32]);
+
+        let (tx, proof) = tx.convolve_commit(&proof, &msg).unwrap();
+        Proof::verify(&proof, &msg, &tx).unwrap();
+
+        let output = tx.outputs().find(|txout| txout.script_pubkey.is_p2tr()).unwrap();
+        let value = proof.commitment_value(&msg).unwrap();
+        assert_eq!(value[..], output.script_pubkey[2..]);
+
+        let other_msg = Commitment::from([9u8; 32]);
+        assert_ne!(proof.commitment_value(&other_msg), Some(value));
+        assert!(Proof::verify(&proof, &other_msg, &tx).is_err());
+    }
 }
@@ -19,7 +19,7 @@
 // See the License for the specific language governing permissions and
 // limitations under the License.
 
-use bc::{InternalPk, OutputPk, TapBranchHash, TapLeafHash, TapNodeHash, TapScript};
+use bc::{InternalPk, IntoTapHash, OutputPk, TapBranchHash, TapLeafHash, TapScript};
 use commit_verify::{mpc, CommitVerify, ConvolveCommit, ConvolveCommitProof};
 
 use super::{TapretFirst, TapretNodePartner, TapretPathProof, TapretProof};
@@ -42,6 +42,32 @@ pub enum TapretKeyError {
     IncorrectOrdering(TapretNodePartner, TapLeafHash),
 }
 
+/// Checks whether the `output_pk` may be a tapret commitment to the `msg` made
+/// with the `internal_pk` over a script tree with `original_merkle_root` (or
+/// over a key-only output, if the root is `None`).
+///
+/// Since the nonce used by the commitment is not known, the function tries all
+/// possible nonce values, requiring up to 256 key tweaks.
+pub fn is_tapret_output(
+    output_pk: OutputPk,
+    internal_pk: InternalPk,
+    original_merkle_root: Option<impl IntoTapHash>,
+    msg: &mpc::Commitment,
+) -> bool {
+    let original_merkle_root = original_merkle_root.map(IntoTapHash::into_tap_hash);
+    (0..=u8::MAX).any(|nonce| {
+        let tapret_commitment = TapretCommitment::with(*msg, nonce);
+        let commitment_hash = TapScript::commit(&tapret_commitment)
+            .tap_leaf_hash()
+            .into_tap_hash();
+        let merkle_root = match original_merkle_root {
+            Some(root) => TapBranchHash::with_nodes(commitment_hash, root).into_tap_hash(),
+            None => commitment_hash,
+        };
+        internal_pk.to_output_pk(Some(merkle_root)).0 == output_pk
+    })
+}
+
 impl ConvolveCommitProof<mpc::Commitment, InternalPk, TapretFirst> for TapretProof {
     type Suppl = TapretPathProof;
 
@@ -50,6 +76,11 @@ impl ConvolveCommitProof<mpc::Commitment, InternalPk, TapretFirst> for TapretPro
     fn extract_supplement(&self) -> &Self::Suppl { &self.path_proof }
 }
 
+/// Tapret commitment always introduces a script tree: when the original output
+/// is key-only, the commitment leaf becomes the only leaf of the new tree (see
+/// [`TapretPathProof::commitment_merkle_root`]). Thus, the produced output key
+/// is never a key-only tweak of the internal key, and the verification, which
+/// reconstructs the output key in the same way, rejects key-only outputs.
 impl ConvolveCommit<mpc::Commitment, TapretProof, TapretFirst> for InternalPk {
     type Commitment = OutputPk;
     type CommitError = TapretKeyError;
@@ -59,26 +90,7 @@ impl ConvolveCommit<mpc::Commitment, TapretProof, TapretFirst> for InternalPk {
         supplement: &TapretPathProof,
         msg: &mpc::Commitment,
     ) -> Result<(OutputPk, TapretProof), Self::CommitError> {
-        let tapret_commitment = TapretCommitment::with(*msg, supplement.nonce);
-        let script_commitment = TapScript::commit(&tapret_commitment);
-
-        let merkle_root: TapNodeHash = if let Some(ref partner) = supplement.partner_node {
-            if !partner.check_no_commitment() {
-                return Err(TapretKeyError::AlternativeCommitment(partner.clone()));
-            }
-
-            let commitment_leaf = script_commitment.tap_leaf_hash();
-            let commitment_hash = TapNodeHash::from(commitment_leaf);
-
-            if !partner.check_ordering(commitment_hash) {
-                return Err(TapretKeyError::IncorrectOrdering(partner.clone(), commitment_leaf));
-            }
-
-            TapBranchHash::with_nodes(commitment_hash, partner.tap_node_hash()).into()
-        } else {
-            TapLeafHash::with_tap_script(&script_commitment).into()
-        };
-
+        let merkle_root = supplement.commitment_merkle_root(msg)?;
         let (output_key, _) = self.to_output_pk(Some(merkle_root));
 
         let proof = TapretProof {
@@ -92,19 +104,15 @@ impl ConvolveCommit<mpc::Commitment, TapretProof, TapretFirst> for InternalPk {
 
 #[cfg(test)]
 mod test {
-    use std::str::FromStr;
-
-    use bc::LeafScript;
+    use bc::{LeafScript, TapNodeHash};
     use commit_verify::mpc::Commitment;
 
     use super::*;
+    use crate::test_helpers::internal_pk;
 
     #[test]
     fn key_path() {
-        let internal_pk = InternalPk::from_str(
-            "c5f93479093e2b8f724a79844cc10928dd44e9a390b539843fb83fbf842723f3",
-        )
-        .unwrap();
+        let internal_pk = internal_pk();
         let msg = mpc::Commitment::from([8u8; 32]);
         let path_proof = TapretPathProof::root(0);
 
@@ -120,22 +128,22 @@ mod test {
         assert_eq!(outer_key, real_key);
 
         assert_eq!(proof, TapretProof {
-            path_proof,
+            path_proof: path_proof.clone(),
             internal_pk
         });
 
-        ConvolveCommitProof::<Commitment, InternalPk, TapretFirst>::verify(
-            &proof, &msg, &outer_key,
-        )
-        .unwrap();
+        let commitment = assert_commit_verify!(
+            convolve: ConvolveCommitProof<Commitment, InternalPk, TapretFirst>,
+            internal_pk,
+            path_proof,
+            msg
+        );
+        assert_eq!(commitment, (outer_key, proof));
     }
 
     #[test]
     fn single_script() {
-        let internal_pk = InternalPk::from_str(
-            "c5f93479093e2b8f724a79844cc10928dd44e9a390b539843fb83fbf842723f3",
-        )
-        .unwrap();
+        let internal_pk = internal_pk();
         let msg = mpc::Commitment::from([8u8; 32]);
         let path_proof = TapretPathProof::with(
             TapretNodePartner::RightLeaf(LeafScript::from_tap_script(default!())),
@@ -146,23 +154,76 @@ mod test {
         let (outer_key, proof) = internal_pk.convolve_commit(&path_proof, &msg).unwrap();
 
         assert_eq!(proof, TapretProof {
-            path_proof,
+            path_proof: path_proof.clone(),
             internal_pk
         });
 
-        ConvolveCommitProof::<Commitment, InternalPk, TapretFirst>::verify(
-            &proof, &msg, &outer_key,
-        )
-        .unwrap();
+        let commitment = assert_commit_verify!(
+            convolve: ConvolveCommitProof<Commitment, InternalPk, TapretFirst>,
+            internal_pk,
+            path_proof,
+            msg
+        );
+        assert_eq!(commitment, (outer_key, proof));
+    }
+
+    #[test]
+    fn tapret_output() {
+        let internal_pk = internal_pk();
+        let msg = mpc::Commitment::from([8u8; 32]);
+
+        let (output_pk, _) = internal_pk
+            .convolve_commit(&TapretPathProof::root(5), &msg)
+            .unwrap();
+        assert!(is_tapret_output(output_pk, internal_pk, None::<TapNodeHash>, &msg));
+        assert!(!is_tapret_output(
+            output_pk,
+            internal_pk,
+            None::<TapNodeHash>,
+            &mpc::Commitment::from([9u8; 32])
+        ));
+
+        let partner = TapretNodePartner::RightLeaf(LeafScript::from_tap_script(default!()));
+        let path_proof = TapretPathProof::with(partner.clone(), 1).unwrap();
+        let (output_pk, _) = internal_pk.convolve_commit(&path_proof, &msg).unwrap();
+        let root = partner.tap_node_hash();
+        assert!(is_tapret_output(output_pk, internal_pk, Some(root), &msg));
+        assert!(!is_tapret_output(output_pk, internal_pk, None::<TapNodeHash>, &msg));
+
+        // plain p2tr outputs are not tapret commitments
+        let (output_pk, _) = internal_pk.to_output_pk_with_root(None);
+        assert!(!is_tapret_output(output_pk, internal_pk, None::<TapNodeHash>, &msg));
+        let (output_pk, _) = internal_pk.to_output_pk(Some(root));
+        assert!(!is_tapret_output(output_pk, internal_pk, Some(root), &msg));
+    }
+
+    #[test]
+    fn key_only_rejected() {
+        let internal_pk = internal_pk();
+        let msg = mpc::Commitment::from([8u8; 32]);
+        let (key_only, _) = internal_pk.to_output_pk_with_root(None);
+
+        for nonce in [0, 1, u8::MAX] {
+            // embedding always adds the commitment leaf
+            let path_proof = TapretPathProof::root(nonce);
+            let (output_pk, proof) = internal_pk.convolve_commit(&path_proof, &msg).unwrap();
+            assert_ne!(output_pk, key_only);
+            assert!(proof.path_proof.commitment_merkle_root(&msg).is_ok());
+
+            // key-only output doesn't verify against a tapret proof
+            assert!(ConvolveCommitProof::<Commitment, InternalPk, TapretFirst>::verify(
+                &proof, &msg, &key_only
+            )
+            .is_err());
+            assert_ne!(proof.committed_output_pk(&msg), Ok(key_only));
+        }
+        assert!(!is_tapret_output(key_only, internal_pk, None::<TapNodeHash>, &msg));
     }
 
     #[test]
     #[should_panic(expected = "IncorrectOrdering")]
     fn invalid_partner_ordering() {
-        let internal_pk = InternalPk::from_str(
-            "c5f93479093e2b8f724a79844cc10928dd44e9a390b539843fb83fbf842723f3",
-        )
-        .unwrap();
+        let internal_pk = internal_pk();
         let msg = mpc::Commitment::from([8u8; 32]);
         let path_proof = TapretPathProof::with(
             TapretNodePartner::RightLeaf(LeafScript::from_tap_script(default!())),
@@ -0,0 +1,72 @@
+// Deterministic bitcoin commitments library.
+//
+// SPDX-License-Identifier: Apache-2.0
+//
+// Written in 2019-2024 by
+//     Dr Maxim Orlovsky <orlovsky@lnp-bp.org>
+//
+// Copyright (C) 2019-2024 LNP/BP Standards Association. All rights reserved.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Helpers shared by the tests of the commitment schemes.
+
+use std::str::FromStr;
+
+use amplify::ByteArray;
+use bc::InternalPk;
+
+/// Checks the full commit-verify cycle of a commitment scheme: commits to the
+/// message, verifies the commitment and checks that the verification fails for
+/// a tampered message.
+///
+/// Embed-commit schemes are invoked as `assert_commit_verify!(embed,
+/// container, msg)` and return the committed container with the proof.
+/// Convolve-commit schemes must provide the proof trait instance and the
+/// supplement: `assert_commit_verify!(convolve: Trait<..>, container,
+/// supplement, msg)`, returning the commitment with the proof.
+macro_rules! assert_commit_verify {
+    (embed, $container:expr, $msg:expr) => {{
+        let msg = $msg;
+        let mut container = $container;
+        let proof = commit_verify::EmbedCommitVerify::embed_commit(&mut container, &msg)
+            .expect("commitment must succeed");
+        assert!(commit_verify::EmbedCommitVerify::verify(&container, &msg, &proof).is_ok());
+        let tampered = $crate::test_helpers::tamper(&msg);
+        assert!(commit_verify::EmbedCommitVerify::verify(&container, &tampered, &proof).is_err());
+        (container, proof)
+    }};
+    (convolve: $scheme:path, $container:expr, $supplement:expr, $msg:expr) => {{
+        let msg = $msg;
+        let (commitment, proof) =
+            commit_verify::ConvolveCommit::convolve_commit(&$container, &$supplement, &msg)
+                .expect("commitment must succeed");
+        assert!(<_ as $scheme>::verify(&proof, &msg, &commitment).is_ok());
+        let tampered = $crate::test_helpers::tamper(&msg);
+        assert!(<_ as $scheme>::verify(&proof, &tampered, &commitment).is_err());
+        (commitment, proof)
+    }};
+}
+
+/// Returns internal key used across the tests of the taproot-based schemes.
+pub fn internal_pk() -> InternalPk {
+    InternalPk::from_str("c5f93479093e2b8f724a79844cc10928dd44e9a390b539843fb83fbf842723f3")
+        .unwrap()
+}
+
+/// Returns a message differing from the provided one in the first byte.
+pub fn tamper<T: ByteArray<32>>(msg: &T) -> T {
+    let mut bytes = msg.to_byte_array();
+    bytes[0] ^= 0xFF;
+    T::from_byte_array(bytes)
+}
@@ -0,0 +1,45 @@
+// Bitcoin protocol single-use-seals library.
+//
+// SPDX-License-Identifier: Apache-2.0
+//
+// Written in 2019-2024 by
+//     Dr Maxim Orlovsky <orlovsky@lnp-bp.org>
+//
+// Copyright (C) 2019-2024 LNP/BP Standards Association. All rights reserved.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+mod util;
+
+use std::hint::black_box;
+
+use commit_verify::Conceal;
+use seals::txout::{BlindSeal, CloseMethod, TxPtr};
+use seals::SecretSeal;
+
+const BATCH_SIZE: u32 = 10_000;
+
+fn reveals() -> Vec<BlindSeal<TxPtr>> {
+    (0..BATCH_SIZE)
+        .map(|no| BlindSeal::new_random(CloseMethod::TapretFirst, TxPtr::WitnessTx, no))
+        .collect()
+}
+
+fn main() {
+    util::bench("conceal/loop", reveals, |reveals| {
+        reveals.iter().map(|seal| seal.conceal()).collect::<Vec<_>>()
+    });
+    util::bench("conceal/commit_many", reveals, |reveals| {
+        SecretSeal::commit_many(black_box(&reveals))
+    });
+}
@@ -0,0 +1,47 @@
+// Bitcoin protocol single-use-seals library.
+//
+// SPDX-License-Identifier: Apache-2.0
+//
+// Written in 2019-2024 by
+//     Dr Maxim Orlovsky <orlovsky@lnp-bp.org>
+//
+// Copyright (C) 2019-2024 LNP/BP Standards Association. All rights reserved.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Minimal benchmark harness not requiring any dependencies, such that the
+//! benchmarks compile with the crate MSRV.
+//!
+//! When the benchmark binary is run by `cargo bench`, each routine is measured
+//! over multiple rounds; otherwise (e.g. under `cargo test --all-targets`) each
+//! routine is run once as a smoke test.
+
+use std::hint::black_box;
+use std::time::{Duration, Instant};
+
+/// Number of measured rounds per routine under `cargo bench`.
+const ROUNDS: u32 = 100;
+
+/// Runs `routine` on the input produced by `setup` and prints the mean time
+/// of the routine execution, excluding the setup.
+pub fn bench<I, O>(name: &str, mut setup: impl FnMut() -> I, mut routine: impl FnMut(I) -> O) {
+    let rounds = if std::env::args().any(|arg| arg == "--bench") { ROUNDS } else { 1 };
+    let mut total = Duration::ZERO;
+    for _ in 0..rounds {
+        let input = setup();
+        let start = Instant::now();
+        black_box(routine(black_box(input)));
+        total += start.elapsed();
+    }
+    println!("{name:<40} {:>12.3?}/iter", total / rounds);
+}
@@ -0,0 +1,85 @@
+// Bitcoin protocol single-use-seals library.
+//
+// SPDX-License-Identifier: Apache-2.0
+//
+// Written in 2019-2024 by
+//     Dr Maxim Orlovsky <orlovsky@lnp-bp.org>
+//
+// Copyright (C) 2019-2024 LNP/BP Standards Association. All rights reserved.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+#[macro_use]
+extern crate amplify;
+
+mod util;
+
+use bc::{LockTime, Outpoint, SeqNo, Tx, TxIn, TxVer, Txid, VarIntArray};
+use seals::txout::{BlindSeal, CloseMethod, SealVerifier, TxPtr, TxoSeal};
+
+const TX_COUNT: u8 = 100;
+const INPUT_COUNT: u32 = 100;
+
+fn witness_txs() -> Vec<Tx> {
+    (0..TX_COUNT)
+        .map(|no| {
+            let inputs = (0..INPUT_COUNT)
+                .map(|vout| TxIn {
+                    prev_output: Outpoint::new(Txid::from([no; 32]), vout),
+                    sig_script: none!(),
+                    sequence: SeqNo::from_consensus_u32(0xFFFFFFFF),
+                    witness: none!(),
+                })
+                .collect::<Vec<_>>();
+            Tx {
+                version: TxVer::V2,
+                inputs: VarIntArray::try_from(inputs).unwrap(),
+                outputs: none!(),
+                lock_time: LockTime::ZERO,
+            }
+        })
+        .collect()
+}
+
+fn seals() -> Vec<BlindSeal<TxPtr>> {
+    (0..TX_COUNT)
+        .flat_map(|no| {
+            (0..INPUT_COUNT).map(move |vout| {
+                BlindSeal::new_random(CloseMethod::TapretFirst, Txid::from([no; 32]), vout)
+            })
+        })
+        .collect()
+}
+
+fn setup() -> (Vec<Tx>, Vec<BlindSeal<TxPtr>>) { (witness_txs(), seals()) }
+
+fn main() {
+    util::bench("verify/naive_scan", setup, |(txs, seals)| {
+        seals
+            .iter()
+            .map(|seal| {
+                let outpoint = seal.outpoint();
+                txs.iter()
+                    .find(|tx| tx.inputs.iter().any(|txin| Some(txin.prev_output) == outpoint))
+                    .map(Tx::txid)
+            })
+            .collect::<Vec<_>>()
+    });
+    util::bench("verify/seal_verifier", setup, |(txs, seals)| {
+        let verifier = SealVerifier::with(&txs);
+        verifier
+            .verify_all(seals)
+            .map(|(_, res)| res.ok())
+            .collect::<Vec<_>>()
+    });
+}
@@ -32,6 +32,14 @@
 #![cfg_attr(docsrs, feature(doc_auto_cfg))]
 
 //! The library provides single-use-seal implementations for bitcoin protocol.
+//!
+//! The generic single-use-seal abstraction, independent from the seal medium,
+//! is defined by the `single_use_seals` crate. Seals defined over bitcoin
+//! transaction outputs ([`txout`]) plug into it with [`txout::Witness`]
+//! implementing its `SealWitness` trait. Closing a seal means constructing
+//! and publishing a witness transaction spending the seal output and
+//! containing a deterministic bitcoin commitment, which is a wallet
+//! responsibility and thus is not a part of this library.
 
 #[macro_use]
 extern crate amplify;
@@ -47,7 +55,7 @@ pub mod resolver;
 pub mod txout;
 mod secret;
 
-pub use secret::SecretSeal;
+pub use secret::{SecretSeal, SecretSealParseError};
 
 /// Method for closing BP single-use-seals.
 pub trait SealCloseMethod: dbc::DbcMethod {}
@@ -21,7 +21,10 @@
 
 //! API for resolving single-use-seals.
 
-use bc::{Tx, Txid};
+use bc::{Outpoint, Tx, Txid, Vout};
+
+use crate::txout::TxoSeal;
+use crate::SealCloseMethod;
 
 /// Error resolving single-use-seal
 #[derive(Debug, Display)]
@@ -33,6 +36,10 @@ pub enum Error {
 
     /// transaction with id {0} is not known to the resolver.
     UnknownTx(Txid),
+
+    /// seal at witness transaction output {0} doesn't define transaction id
+    /// and can't be resolved.
+    NoSealTxid(Vout),
 }
 
 /// API which must be provided by a resolver to operate with single-use-seal.
@@ -40,3 +47,127 @@ pub trait Resolver {
     /// Return transaction data for a given transaction id.
     fn tx_by_id(&self, txid: Txid) -> Result<Tx, Error>;
 }
+
+/// Status of a single-use-seal.
+#[derive(Copy, Clone, Eq, PartialEq, Hash, Debug, Display)]
+pub enum SealStatus {
+    /// The seal is not closed.
+    #[display("open")]
+    Open,
+
+    /// The seal is closed by a transaction which is not mined yet.
+    #[display("closed by unconfirmed {0}")]
+    ClosedUnconfirmed(Txid),
+
+    /// The seal is closed by a transaction mined at a given block height.
+    #[display("closed by {0} at height {1}")]
+    ClosedConfirmed(Txid, u32),
+}
+
+/// API for providing information about transactions spending outputs, both
+/// mined and unconfirmed.
+pub trait TxProvider {
+    /// Returns id of a mined or unconfirmed transaction spending the
+    /// `outpoint`, if any.
+    fn spending_txid(&self, outpoint: Outpoint) -> Result<Option<Txid>, Error>;
+
+    /// Returns height of the block containing the transaction, or `None` if the
+    /// transaction is not mined yet.
+    ///
+    /// # Errors
+    ///
+    /// [`Error::UnknownTx`] if the transaction is not known to the provider.
+    fn tx_height(&self, txid: Txid) -> Result<Option<u32>, Error>;
+}
+
+/// Resolves status of the seal using the transaction provider.
+///
+/// # Errors
+///
+/// [`Error::NoSealTxid`] if the seal points to a witness transaction and
+/// doesn't define transaction id; otherwise errors returned by the provider.
+pub fn seal_status<M: SealCloseMethod>(
+    seal: &impl TxoSeal<M>,
+    txs: &impl TxProvider,
+) -> Result<SealStatus, Error> {
+    let outpoint = seal.outpoint().ok_or(Error::NoSealTxid(seal.vout()))?;
+    let Some(txid) = txs.spending_txid(outpoint)? else {
+        return Ok(SealStatus::Open);
+    };
+    Ok(match txs.tx_height(txid)? {
+        None => SealStatus::ClosedUnconfirmed(txid),
+        Some(height) => SealStatus::ClosedConfirmed(txid, height),
+    })
+}
+
+#[cfg(test)]
+mod test {
+    use std::collections::BTreeMap;
+
+    use super::*;
+    use crate::txout::{BlindSeal, CloseMethod, TxPtr};
+
+    #[derive(Default)]
+    struct MockProvider {
+        spenders: BTreeMap<Outpoint, Txid>,
+        heights: BTreeMap<Txid, Option<u32>>,
+    }
+
+    impl TxProvider for MockProvider {
+        fn spending_txid(&self, outpoint: Outpoint) -> Result<Option<Txid>, Error> {
+            Ok(self.spenders.get(&outpoint).copied())
+        }
+
+        fn tx_height(&self, txid: Txid) -> Result<Option<u32>, Error> {
+            self.heights.get(&txid).copied().ok_or(Error::UnknownTx(txid))
+        }
+    }
+
+    #[test]
+    fn seal_statuses() {
+        let prev_txid = Txid::from([0xA5; 32]);
+        let mempool_txid = Txid::from([1u8; 32]);
+        let mined_txid = Txid::from([2u8; 32]);
+        let unknown_txid = Txid::from([3u8; 32]);
+        let mut provider = MockProvider::default();
+        provider
+            .spenders
+            .insert(Outpoint::new(prev_txid, 1), mempool_txid);
+        provider
+            .spenders
+            .insert(Outpoint::new(prev_txid, 2), mined_txid);
+        provider
+            .spenders
+            .insert(Outpoint::new(prev_txid, 3), unknown_txid);
+        provider.heights.insert(mempool_txid, None);
+        provider.heights.insert(mined_txid, Some(800_000));
+
+        let seal = |vout: u32| {
+            BlindSeal::<TxPtr>::with_blinding(CloseMethod::TapretFirst, prev_txid, vout, 0)
+        };
+        assert!(matches!(seal_status(&seal(0), &provider), Ok(SealStatus::Open)));
+        assert!(matches!(
+            seal_status(&seal(1), &provider),
+            Ok(SealStatus::ClosedUnconfirmed(txid)) if txid == mempool_txid
+        ));
+        assert!(matches!(
+            seal_status(&seal(2), &provider),
+            Ok(SealStatus::ClosedConfirmed(txid, 800_000)) if txid == mined_txid
+        ));
+        assert!(matches!(
+            seal_status(&seal(3), &provider),
+            Err(Error::UnknownTx(txid)) if txid == unknown_txid
+        ));
+
+        let witness_seal = BlindSeal::<TxPtr>::with_blinding(
+            CloseMethod::TapretFirst,
+            TxPtr::WitnessTx,
+            4u32,
+            0,
+        );
+        assert!(matches!(
+            seal_status(&witness_seal, &provider),
+            Err(Error::NoSealTxid(vout)) if vout == Vout::from(4)
+        ));
+    }
+}
@@ -24,7 +24,25 @@ use std::str::FromStr;
 
 use amplify::{ByteArray, Bytes32, Wrapper};
 use baid64::{Baid64ParseError, DisplayBaid64, FromBaid64Str};
-use commit_verify::{CommitmentId, DigestExt, Sha256};
+use bc::{Outpoint, TaggedHasher, Vout};
+use commit_verify::{CommitmentId, Conceal, DigestExt, Sha256};
+use strict_encoding::{StreamWriter, StrictEncode};
+
+use crate::txout::{BlindSeal, CloseMethod, SealTxid, WideBlindSeal};
+
+/// Errors parsing [`SecretSeal`] from a string.
+#[derive(Clone, PartialEq, Eq, Debug, Display, Error, From)]
+#[display(doc_comments)]
+pub enum SecretSealParseError {
+    /// invalid secret seal string. {0}
+    #[from]
+    Baid64(Baid64ParseError),
+
+    /// '{0}' is a transaction outpoint and not a secret seal. Secret seal must
+    /// be produced by concealing a seal definition with a blinding factor by
+    /// the seal owner.
+    LooksLikeOutpoint(String),
+}
 
 /// Confidential version of transaction outpoint-based single-use-seal
 #[derive(Wrapper, Copy, Clone, PartialEq, Eq, PartialOrd, Ord, Hash, Debug, From)]
@@ -50,6 +68,151 @@ impl From<Sha256> for SecretSeal {
     fn from(hasher: Sha256) -> Self { hasher.finish().into() }
 }
 
+impl SecretSeal {
+    /// Verifies that the secret seal is a concealed form of the provided
+    /// revealed seal definition. The comparison is performed in a constant
+    /// time.
+    pub fn verify_reveal<Id: SealTxid>(&self, reveal: &BlindSeal<Id>) -> bool {
+        let expected = reveal.conceal().to_byte_array();
+        let diff = self
+            .to_byte_array()
+            .iter()
+            .zip(expected)
+            .fold(0u8, |acc, (a, b)| acc | (a ^ b));
+        diff == 0
+    }
+
+    /// Always returns `None`: secret seal is a one-way commitment (a tagged
+    /// hash) to the seal definition, which can't be recovered from it.
+    ///
+    /// The method exists only to document this property. The seal definition
+    /// must be obtained from its owner; to check that a known definition
+    /// matches the secret seal use [`SecretSeal::verify_reveal`].
+    #[inline]
+    pub fn try_reveal<Id: SealTxid>(&self) -> Option<BlindSeal<Id>> { None }
+
+    /// Conceals revealed seal definition using a custom commitment tag instead
+    /// of the standard [`SecretSeal::TAG`], producing secret seals in a
+    /// distinct namespace. Allows protocols not compatible with RGB to avoid
+    /// collisions of their secret seals with the RGB ones.
+    ///
+    /// Standard concealment procedure ([`Conceal::conceal`]) is not affected
+    /// and always uses [`SecretSeal::TAG`].
+    pub fn commit_tagged<Id: SealTxid>(reveal: &BlindSeal<Id>, tag: &str) -> Self {
+        Self::commit_using(&TaggedHasher::<Sha256>::new(tag), reveal)
+    }
+
+    /// Conceals multiple revealed seal definitions at once. Produces the same
+    /// secret seals as [`Conceal::conceal`] called on each of the definitions,
+    /// but initializes the tagged hasher midstate only once, which is faster
+    /// for large batches of seals.
+    pub fn commit_many<Id: SealTxid>(reveals: &[BlindSeal<Id>]) -> Vec<Self> {
+        let hasher = TaggedHasher::<Sha256>::new(Self::TAG);
+        reveals
+            .iter()
+            .map(|reveal| Self::commit_using(&hasher, reveal))
+            .collect()
+    }
+
+    /// Version 2 concealment tag for seals closed with
+    /// [`CloseMethod::OpretFirst`].
+    pub const TAG_V2_OPRET: &'static str = "urn:lnp-bp:lnpbp10:secret-opret1st#v2";
+
+    /// Version 2 concealment tag for seals closed with
+    /// [`CloseMethod::TapretFirst`].
+    pub const TAG_V2_TAPRET: &'static str = "urn:lnp-bp:lnpbp10:secret-tapret1st#v2";
+
+    /// Version 2 concealment tag for [`WideBlindSeal`]s closed with
+    /// [`CloseMethod::OpretFirst`].
+    pub const TAG_V2_WIDE_OPRET: &'static str = "urn:lnp-bp:lnpbp10:secret-wide-opret1st#v2";
+
+    /// Version 2 concealment tag for [`WideBlindSeal`]s closed with
+    /// [`CloseMethod::TapretFirst`].
+    pub const TAG_V2_WIDE_TAPRET: &'static str = "urn:lnp-bp:lnpbp10:secret-wide-tapret1st#v2";
+
+    /// Returns commitment tag used by [`SecretSeal::commit_v2`] for seals
+    /// with a given close method.
+    pub const fn tag_v2(method: CloseMethod) -> &'static str {
+        match method {
+            CloseMethod::OpretFirst => Self::TAG_V2_OPRET,
+            CloseMethod::TapretFirst => Self::TAG_V2_TAPRET,
+        }
+    }
+
+    /// Returns commitment tag used for concealing [`WideBlindSeal`]s with a
+    /// given close method.
+    pub const fn tag_v2_wide(method: CloseMethod) -> &'static str {
+        match method {
+            CloseMethod::OpretFirst => Self::TAG_V2_WIDE_OPRET,
+            CloseMethod::TapretFirst => Self::TAG_V2_WIDE_TAPRET,
+        }
+    }
+
+    /// Conceals revealed seal definition using version 2 of the concealment
+    /// procedure.
+    ///
+    /// Unlike the standard concealment ([`Conceal::conceal`]), which hashes
+    /// the seal close method as the first byte of the committed data, version
+    /// 2 binds the seal to its close method via a distinct per-method
+    /// commitment tag ([`SecretSeal::tag_v2`]) and commits only to the txid,
+    /// vout and blinding factor. Thus the concealed value does not depend on
+    /// the encoding of the close method. [`WideBlindSeal`]s are concealed with
+    /// the same procedure, using their own tags ([`SecretSeal::tag_v2_wide`]).
+    ///
+    /// Version 2 secret seals are not compatible with the standard ones: the
+    /// same seal definition produces different secret seals, and verifiers
+    /// must know which version was used.
+    pub fn commit_v2<Id: SealTxid>(reveal: &BlindSeal<Id>) -> Self {
+        Self::commit_v2_using::<Sha256, _>(reveal)
+    }
+
+    /// Conceals revealed seal definition using version 2 of the concealment
+    /// procedure ([`SecretSeal::commit_v2`]), computing the tagged hash with a
+    /// custom SHA-256 backend `D`.
+    pub fn commit_v2_using<D: DigestExt + Clone, Id: SealTxid>(reveal: &BlindSeal<Id>) -> Self {
+        let hasher = TaggedHasher::<D>::new(Self::tag_v2(reveal.method));
+        Self::commit_v2_with(&hasher, reveal.txid, reveal.vout, reveal.blinding)
+    }
+
+    /// Implements version 2 of the concealment procedure, committing to the
+    /// strict encoding of the seal txid, vout and blinding factor with the
+    /// provided tagged hasher.
+    pub(crate) fn commit_v2_with<D: DigestExt + Clone>(
+        hasher: &TaggedHasher<D>,
+        txid: impl StrictEncode,
+        vout: Vout,
+        blinding: impl StrictEncode,
+    ) -> Self {
+        const ERR: &str = "seal definition must fit 64 bytes";
+        let mut writer = StreamWriter::in_memory::<64>();
+        txid.strict_write(&mut writer).expect(ERR);
+        vout.strict_write(&mut writer).expect(ERR);
+        blinding.strict_write(&mut writer).expect(ERR);
+        hasher.hash(&[&writer.unconfine()]).into()
+    }
+
+    /// Conceals revealed seal definition with the provided tagged hasher,
+    /// which allows using a custom SHA-256 backend `D`. With a hasher for
+    /// [`SecretSeal::TAG`] the result matches [`Conceal::conceal`].
+    pub fn commit_using<D: DigestExt + Clone, Id: SealTxid>(
+        hasher: &TaggedHasher<D>,
+        reveal: &BlindSeal<Id>,
+    ) -> Self {
+        let mut writer = StreamWriter::in_memory::<64>();
+        reveal
+            .strict_write(&mut writer)
+            .expect("seal definition must fit 64 bytes");
+        hasher.hash(&[&writer.unconfine()]).into()
+    }
+
+    /// Returns short seal identifier for logging, made of the first 8 hex
+    /// digits of the secret seal followed by an ellipsis.
+    pub fn fingerprint(&self) -> String {
+        let bytes = self.to_byte_array();
+        format!("{:08x}…", u32::from_be_bytes([bytes[0], bytes[1], bytes[2], bytes[3]]))
+    }
+}
+
 impl DisplayBaid64 for SecretSeal {
     const HRI: &'static str = "utxob";
     const CHUNKING: bool = true;
@@ -59,9 +222,20 @@ impl DisplayBaid64 for SecretSeal {
     fn to_baid64_payload(&self) -> [u8; 32] { self.to_byte_array() }
 }
 impl FromBaid64Str for SecretSeal {}
+/// Parses secret seal from its Baid64 representation.
+///
+/// Transaction outpoints (`txid:vout`) are explicitly rejected with
+/// [`SecretSealParseError::LooksLikeOutpoint`]: a secret seal can't be derived
+/// from a bare outpoint without choosing a close method and a blinding factor,
+/// which must be done by the seal owner.
 impl FromStr for SecretSeal {
-    type Err = Baid64ParseError;
-    fn from_str(s: &str) -> Result<Self, Self::Err> { Self::from_baid64_str(s) }
+    type Err = SecretSealParseError;
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        if Outpoint::from_str(s).is_ok() {
+            return Err(SecretSealParseError::LooksLikeOutpoint(s.to_owned()));
+        }
+        Self::from_baid64_str(s).map_err(SecretSealParseError::from)
+    }
 }
 impl Display for SecretSeal {
     fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result { self.fmt_baid64(f) }
@@ -70,6 +244,36 @@ impl Display for SecretSeal {
 #[cfg(test)]
 mod test {
     use super::*;
+    use crate::txout::TxPtr;
+
+    #[test]
+    fn constructors_consistent() {
+        use std::collections::hash_map::DefaultHasher;
+        use std::hash::{Hash, Hasher};
+
+        use amplify::hex::{FromHex, ToHex};
+
+        fn hash_of(seal: &SecretSeal) -> u64 {
+            let mut hasher = DefaultHasher::new();
+            seal.hash(&mut hasher);
+            hasher.finish()
+        }
+
+        let bytes: [u8; 32] = core::array::from_fn(|i| i as u8);
+        let hex = "000102030405060708090a0b0c0d0e0f101112131415161718191a1b1c1d1e1f";
+        let seal = SecretSeal::from(bytes);
+        let seals = [
+            SecretSeal::from(Bytes32::from(bytes)),
+            SecretSeal::from_hex(hex).unwrap(),
+            SecretSeal::from_str(&seal.to_string()).unwrap(),
+        ];
+        for other in seals {
+            assert_eq!(other, seal);
+            assert_eq!(hash_of(&other), hash_of(&seal));
+            assert_eq!(other.to_hex(), hex);
+        }
+        assert_eq!(seal.to_byte_array(), bytes);
+    }
 
     #[test]
     fn secret_seal_baid64() {
@@ -80,4 +284,166 @@ mod test {
         let reconstructed = SecretSeal::from_str(&baid64.replace('-', "")).unwrap();
         assert_eq!(reconstructed, seal);
     }
+
+    #[test]
+    fn outpoint_shaped_input() {
+        let outpoint = "646ca5c1062619e2a2d60771c9dfd820551fb773e4dc8c4ed67965a8d1fae839:1";
+        assert_eq!(
+            SecretSeal::from_str(outpoint),
+            Err(SecretSealParseError::LooksLikeOutpoint(outpoint.to_owned()))
+        );
+        let txid = &outpoint[..64];
+        assert!(matches!(SecretSeal::from_str(txid), Err(SecretSealParseError::Baid64(_))));
+    }
+
+    #[test]
+    fn fingerprint() {
+        let seal = SecretSeal::from([0xA5; 32]);
+        assert_eq!(seal.fingerprint(), "a5a5a5a5…");
+
+        let reveal = BlindSeal::<TxPtr>::with_blinded_vout(CloseMethod::TapretFirst, 1u32, 0xdead);
+        let fingerprint = reveal.conceal().fingerprint();
+        assert_eq!(fingerprint.chars().count(), 9);
+        assert_eq!(fingerprint, reveal.conceal().fingerprint());
+        assert!(fingerprint.starts_with(&format!("{:02x}", reveal.conceal()[0])));
+    }
+
+    #[test]
+    fn verify_reveal() {
+        let reveal = BlindSeal::<TxPtr>::with_blinded_vout(CloseMethod::TapretFirst, 1u32, 0xdead);
+        let secret = reveal.conceal();
+        assert!(secret.verify_reveal(&reveal));
+
+        let mut tampered = reveal;
+        tampered.blinding += 1;
+        assert!(!secret.verify_reveal(&tampered));
+
+        let mut tampered = reveal;
+        tampered.method = CloseMethod::OpretFirst;
+        assert!(!secret.verify_reveal(&tampered));
+    }
+
+    #[test]
+    fn one_way() {
+        let reveal = BlindSeal::<TxPtr>::with_blinded_vout(CloseMethod::TapretFirst, 1u32, 0xdead);
+        let secret = reveal.conceal();
+        assert_eq!(secret.try_reveal::<TxPtr>(), None);
+
+        let same = BlindSeal::<TxPtr>::with_blinded_vout(CloseMethod::TapretFirst, 1u32, 0xdead);
+        assert!(secret.verify_reveal(&same));
+        let others = [
+            BlindSeal::<TxPtr>::with_blinded_vout(CloseMethod::OpretFirst, 1u32, 0xdead),
+            BlindSeal::<TxPtr>::with_blinded_vout(CloseMethod::TapretFirst, 2u32, 0xdead),
+            BlindSeal::<TxPtr>::with_blinded_vout(CloseMethod::TapretFirst, 1u32, 0xbeef),
+            BlindSeal::<TxPtr>::with_blinding(
+                CloseMethod::TapretFirst,
+                TxPtr::Txid(bc::Txid::from([0xA5; 32])),
+                1u32,
+                0xdead,
+            ),
+        ];
+        for other in others {
+            assert_ne!(other, reveal);
+            assert!(!secret.verify_reveal(&other));
+            assert_ne!(other.conceal(), secret);
+        }
+    }
+
+    #[test]
+    fn commit_tagged() {
+        let reveal = BlindSeal::<TxPtr>::with_blinded_vout(CloseMethod::TapretFirst, 1u32, 0xdead);
+        assert_eq!(SecretSeal::commit_tagged(&reveal, SecretSeal::TAG), reveal.conceal());
+
+        let custom1 = SecretSeal::commit_tagged(&reveal, "urn:example:seals:secret#2024-06-01");
+        let custom2 = SecretSeal::commit_tagged(&reveal, "urn:example:seals:secret#2024-06-02");
+        assert_ne!(custom1, reveal.conceal());
+        assert_ne!(custom1, custom2);
+    }
+
+    #[test]
+    fn commit_using() {
+        let reveal = BlindSeal::<TxPtr>::with_blinded_vout(CloseMethod::TapretFirst, 1u32, 0xdead);
+        let hasher = TaggedHasher::<Sha256>::new(SecretSeal::TAG);
+        assert_eq!(SecretSeal::commit_using(&hasher, &reveal), reveal.conceal());
+        assert_eq!(
+            SecretSeal::commit_v2_using::<Sha256, _>(&reveal),
+            SecretSeal::commit_v2(&reveal)
+        );
+    }
+
+    #[test]
+    fn commit_many() {
+        assert!(SecretSeal::commit_many::<TxPtr>(&[]).is_empty());
+
+        let reveals = (0u32..16)
+            .map(|no| {
+                let method =
+                    if no % 2 == 0 { CloseMethod::TapretFirst } else { CloseMethod::OpretFirst };
+                BlindSeal::<TxPtr>::with_blinded_vout(method, no, 0xdead_0000 + no as u64)
+            })
+            .collect::<Vec<_>>();
+        let secrets = SecretSeal::commit_many(&reveals);
+        assert_eq!(secrets.len(), reveals.len());
+        for (reveal, secret) in reveals.iter().zip(&secrets) {
+            assert_eq!(*secret, reveal.conceal());
+        }
+    }
+
+    #[test]
+    fn commit_v2() {
+        use amplify::hex::ToHex;
+
+        let tapret = BlindSeal::<TxPtr>::with_blinded_vout(CloseMethod::TapretFirst, 1u32, 0xdead);
+        let opret = BlindSeal::<TxPtr>::with_blinded_vout(CloseMethod::OpretFirst, 1u32, 0xdead);
+        assert_ne!(SecretSeal::commit_v2(&tapret), SecretSeal::commit_v2(&opret));
+        assert_ne!(SecretSeal::commit_v2(&tapret), tapret.conceal());
+        assert_ne!(SecretSeal::commit_v2(&opret), opret.conceal());
+
+        // The method is bound by a fixed tag, independently of its encoding or
+        // string representation
+        assert_eq!(
+            SecretSeal::tag_v2(CloseMethod::TapretFirst),
+            "urn:lnp-bp:lnpbp10:secret-tapret1st#v2"
+        );
+        assert_eq!(
+            SecretSeal::tag_v2(CloseMethod::OpretFirst),
+            "urn:lnp-bp:lnpbp10:secret-opret1st#v2"
+        );
+        let mut payload = vec![0x00];
+        payload.extend(1u32.to_le_bytes());
+        payload.extend(0xdead_u64.to_le_bytes());
+        for (seal, tag) in [
+            (tapret, "urn:lnp-bp:lnpbp10:secret-tapret1st#v2"),
+            (opret, "urn:lnp-bp:lnpbp10:secret-opret1st#v2"),
+        ] {
+            let expected = SecretSeal::from(TaggedHasher::<Sha256>::new(tag).hash(&[&payload]));
+            assert_eq!(SecretSeal::commit_v2(&seal), expected);
+        }
+
+        // Test vectors
+        assert_eq!(
+            SecretSeal::commit_v2(&tapret).to_hex(),
+            "d21de45c1ff64cca829e386d042d401fa68689cd5d87468893cbaa0ec393245c"
+        );
+        assert_eq!(
+            SecretSeal::commit_v2(&opret).to_hex(),
+            "92f6e703f470b265d39f05a87a971aafb5f0e2b3afa354f6d7b3cfe5a5e142b8"
+        );
+    }
+
+    #[test]
+    fn commit_v2_wide() {
+        let narrow = BlindSeal::<TxPtr>::with_blinded_vout(CloseMethod::TapretFirst, 1u32, 0xdead);
+        let wide = WideBlindSeal::from(narrow);
+        let expected = SecretSeal::commit_v2_with(
+            &TaggedHasher::<Sha256>::new(SecretSeal::TAG_V2_WIDE_TAPRET),
+            TxPtr::WitnessTx,
+            Vout::from(1u32),
+            0xdead_u128,
+        );
+        assert_eq!(wide.conceal(), expected);
+        assert_ne!(wide.conceal(), SecretSeal::commit_v2(&narrow));
+        assert_ne!(SecretSeal::tag_v2_wide(CloseMethod::OpretFirst), SecretSeal::TAG_V2_OPRET);
+        assert_ne!(SecretSeal::tag_v2_wide(CloseMethod::TapretFirst), SecretSeal::TAG_V2_TAPRET);
+    }
 }
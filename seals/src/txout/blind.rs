@@ -25,14 +25,15 @@ use std::fmt::{self, Debug, Display, Formatter};
 use std::hash::Hash;
 use std::str::FromStr;
 
-use amplify::hex;
+use amplify::{hex, ByteArray};
 use bc::{Outpoint, Txid, Vout};
 use commit_verify::{CommitId, Conceal};
 use dbc::MethodParseError;
 use rand::{thread_rng, RngCore};
 use strict_encoding::{StrictDecode, StrictDumb, StrictEncode};
 
-use super::{CloseMethod, WitnessVoutError};
+use super::{CloseMethod, ExplicitTxidError, WitnessVoutError};
+use crate::txout::seal::{parse_txid, parse_vout, OutpointParseError};
 use crate::txout::{SealTxid, TxPtr, TxoSeal};
 use crate::{SealCloseMethod, SecretSeal};
 
@@ -74,6 +75,10 @@ pub struct BlindSeal<Id: SealTxid, M: SealCloseMethod = CloseMethod> {
     pub blinding: u64,
 }
 
+/// Concealment commits to the strict encoding of the seal. For
+/// [`ChainBlindSeal`] it includes [`TxPtr`] variant tag, thus a seal pointing
+/// to a witness transaction never produces the same [`SecretSeal`] as a seal
+/// pointing to a transaction with all-zero txid.
 impl<Id: SealTxid> Conceal for BlindSeal<Id> {
     type Concealed = SecretSeal;
 
@@ -82,19 +87,19 @@ impl<Id: SealTxid> Conceal for BlindSeal<Id> {
 }
 
 impl<M: SealCloseMethod> TryFrom<&BlindSeal<TxPtr, M>> for Outpoint {
-    type Error = WitnessVoutError;
+    type Error = WitnessVoutError<M>;
 
     #[inline]
     fn try_from(reveal: &BlindSeal<TxPtr, M>) -> Result<Self, Self::Error> {
         reveal
             .txid
             .map_to_outpoint(reveal.vout)
-            .ok_or(WitnessVoutError)
+            .ok_or_else(|| WitnessVoutError::for_seal(reveal))
     }
 }
 
 impl<M: SealCloseMethod> TryFrom<BlindSeal<TxPtr, M>> for Outpoint {
-    type Error = WitnessVoutError;
+    type Error = WitnessVoutError<M>;
 
     #[inline]
     fn try_from(reveal: BlindSeal<TxPtr, M>) -> Result<Self, Self::Error> {
@@ -135,6 +140,13 @@ impl<Id: SealTxid, M: SealCloseMethod> TxoSeal<M> for BlindSeal<Id, M> {
 }
 
 impl<Id: SealTxid> BlindSeal<Id, CloseMethod> {
+    /// Returns seal closing method used by default, which is hardcoded to be
+    /// [`CloseMethod::TapretFirst`]. Applications using other method should
+    /// construct seals with [`BlindSeal::from_outpoint_method`] or
+    /// [`BlindSeal::new_random`] providing the method explicitly.
+    #[inline]
+    pub const fn default_method() -> CloseMethod { CloseMethod::TapretFirst }
+
     /// Creates new seal using TapretFirst closing method for the provided
     /// outpoint. Uses `thread_rng` to initialize blinding factor.
     pub fn tapret_first_rand_from(outpoint: Outpoint) -> Self {
@@ -167,6 +179,12 @@ impl<Id: SealTxid, M: SealCloseMethod> BlindSeal<Id, M> {
         BlindSeal::with_rng(method, txid, vout, &mut thread_rng())
     }
 
+    /// Creates new seal for the provided outpoint using an explicitly given
+    /// seal closing method. Uses `thread_rng` to initialize blinding factor.
+    pub fn from_outpoint_method(outpoint: Outpoint, method: M) -> Self {
+        BlindSeal::new_random(method, outpoint.txid, outpoint.vout)
+    }
+
     /// Creates new seal for the provided outpoint and seal closing method. Uses
     /// provided random number generator to create a new blinding factor.
     pub fn with_rng(
@@ -199,9 +217,37 @@ impl<Id: SealTxid, M: SealCloseMethod> BlindSeal<Id, M> {
             blinding,
         }
     }
+
+    /// Returns blinding factor as a `0x`-prefixed hexadecimal string with all
+    /// 16 digits, as used in the seal string representation.
+    #[inline]
+    pub fn blinding_hex(&self) -> String { format!("{:#018x}", self.blinding) }
+
+    /// Parses blinding factor from a `0x`-prefixed hexadecimal string of up to
+    /// 16 digits.
+    pub fn parse_blinding_hex(s: &str) -> Result<u64, ParseError> {
+        let hex = s.strip_prefix("0x").ok_or(ParseError::NonHexBlinding)?;
+        u64::from_str_radix(hex, 16).map_err(|_| ParseError::WrongBlinding)
+    }
+
+    /// Returns short seal identifier for logging, consisting of the close
+    /// method, the first 8 hex digits of the txid (or `~` for witness seals)
+    /// and the output number. The blinding factor is not included.
+    pub fn fingerprint(&self) -> String
+    where M: Display {
+        let txid = match self.txid.txid() {
+            Some(txid) => format!("{}…", &txid.to_string()[..8]),
+            None => self.txid.to_string(),
+        };
+        format!("{}:{}:{}", self.method, txid, self.vout)
+    }
 }
 
 impl<M: SealCloseMethod> BlindSeal<TxPtr, M> {
+    /// Starts construction of a seal with [`BlindSealBuilder`].
+    #[inline]
+    pub fn builder() -> BlindSealBuilder<M> { BlindSealBuilder::default() }
+
     /// Creates new seal pointing to a witness transaction of another seal.
     /// Takes seal closing method and witness transaction output number as
     /// arguments. Uses `thread_rng` to initialize blinding factor.
@@ -246,6 +292,311 @@ impl<M: SealCloseMethod> BlindSeal<TxPtr, M> {
     }
 }
 
+impl BlindSeal<TxPtr, CloseMethod> {
+    /// Flag of the compact representation header byte signalling the presence
+    /// of the transaction id.
+    pub const COMPACT_FLAG_TXID: u8 = 0x80;
+    /// Flag of the compact representation header byte signalling that the
+    /// output number takes a single byte.
+    pub const COMPACT_FLAG_SHORT_VOUT: u8 = 0x40;
+
+    /// Serializes seal into a compact binary representation, which is not
+    /// compatible with the strict encoding and is intended for size-sensitive
+    /// use cases.
+    ///
+    /// The representation consists of:
+    /// - a header byte, where the highest bit
+    ///   ([`BlindSeal::COMPACT_FLAG_TXID`]) signals the presence of the
+    ///   transaction id, the next bit ([`BlindSeal::COMPACT_FLAG_SHORT_VOUT`])
+    ///   signals that the output number is less than 256 and is serialized as
+    ///   a single byte, and the lowest 6 bits hold the close method tag;
+    /// - 32 bytes of the transaction id, if present;
+    /// - output number as a single byte or as 4 bytes in little-endian order;
+    /// - 8 bytes of the blinding factor in little-endian order.
+    ///
+    /// Thus, the representation takes from 10 bytes (witness seals with a
+    /// small output number) to 45 bytes.
+    pub fn to_compact_bytes(&self) -> Vec<u8> {
+        let mut data = Vec::with_capacity(45);
+        let mut header = self.method as u8;
+        if self.txid.txid().is_some() {
+            header |= Self::COMPACT_FLAG_TXID;
+        }
+        let vout = self.vout.into_u32();
+        if vout <= u8::MAX as u32 {
+            header |= Self::COMPACT_FLAG_SHORT_VOUT;
+        }
+        data.push(header);
+        if let Some(txid) = self.txid.txid() {
+            data.extend(txid.to_byte_array());
+        }
+        match u8::try_from(vout) {
+            Ok(vout) => data.push(vout),
+            Err(_) => data.extend(vout.to_le_bytes()),
+        }
+        data.extend(self.blinding.to_le_bytes());
+        data
+    }
+
+    /// Deserializes seal from the compact binary representation produced by
+    /// [`BlindSeal::to_compact_bytes`].
+    ///
+    /// # Errors
+    ///
+    /// If the data length doesn't match the header, the header contains an
+    /// unknown close method or the output number is not serialized in the
+    /// shortest possible form.
+    pub fn from_compact_bytes(data: &[u8]) -> Result<Self, CompactSealError> {
+        let (&header, mut data) = data.split_first().ok_or(CompactSealError::InvalidLength(0))?;
+        let tag = header & !(Self::COMPACT_FLAG_TXID | Self::COMPACT_FLAG_SHORT_VOUT);
+        let method = CloseMethod::ALL
+            .iter()
+            .copied()
+            .find(|method| *method as u8 == tag)
+            .ok_or(CompactSealError::UnknownMethod(tag))?;
+
+        let has_txid = header & Self::COMPACT_FLAG_TXID != 0;
+        let short_vout = header & Self::COMPACT_FLAG_SHORT_VOUT != 0;
+        let len = 1 + if has_txid { 32 } else { 0 } + if short_vout { 1 } else { 4 } + 8;
+        if data.len() + 1 != len {
+            return Err(CompactSealError::InvalidLength(data.len() + 1));
+        }
+
+        let mut txid = TxPtr::WitnessTx;
+        if has_txid {
+            let (bytes, rest) = data.split_at(32);
+            txid = TxPtr::Txid(Txid::from_slice_unsafe(bytes));
+            data = rest;
+        }
+        let vout = if short_vout {
+            let (vout, rest) = data.split_at(1);
+            data = rest;
+            vout[0] as u32
+        } else {
+            let (vout, rest) = data.split_at(4);
+            data = rest;
+            let vout = u32::from_le_bytes(vout.try_into().expect("fixed length"));
+            if vout <= u8::MAX as u32 {
+                return Err(CompactSealError::NonCanonicalVout(vout));
+            }
+            vout
+        };
+        let blinding = u64::from_le_bytes(data.try_into().expect("fixed length"));
+
+        Ok(BlindSeal::with_blinding(method, txid, vout, blinding))
+    }
+}
+
+/// Errors decoding compact binary representation of a seal (see
+/// [`BlindSeal::to_compact_bytes`]).
+#[derive(Copy, Clone, PartialEq, Eq, Hash, Debug, Display, Error)]
+#[display(doc_comments)]
+pub enum CompactSealError {
+    /// compact seal data has invalid length of {0} bytes.
+    InvalidLength(usize),
+
+    /// compact seal data contains unknown close method tag {0:#04x}.
+    UnknownMethod(u8),
+
+    /// compact seal data contains output number {0} which must be serialized
+    /// as a single byte.
+    NonCanonicalVout(u32),
+}
+
+/// Seal definition which always points to an output of the witness transaction.
+///
+/// Equivalent to a [`ChainBlindSeal`] with [`TxPtr::WitnessTx`] transaction
+/// pointer, but guarantees the absence of the transaction id at the type level.
+#[derive(Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Debug)]
+#[derive(StrictType, StrictDumb, StrictEncode, StrictDecode)]
+#[strict_type(lib = dbc::LIB_NAME_BPCORE)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize), serde(crate = "serde_crate"))]
+pub struct WitnessSeal<M: SealCloseMethod = CloseMethod> {
+    /// Commitment to the specific seal close method [`CloseMethod`] which must
+    /// be used to close this seal.
+    pub method: M,
+
+    /// Witness transaction output number.
+    pub vout: Vout,
+
+    /// Blinding factor providing confidentiality of the seal definition.
+    pub blinding: u64,
+}
+
+impl Conceal for WitnessSeal {
+    type Concealed = SecretSeal;
+
+    #[inline]
+    fn conceal(&self) -> Self::Concealed { BlindSeal::<TxPtr>::from(*self).conceal() }
+}
+
+impl<M: SealCloseMethod> From<WitnessSeal<M>> for BlindSeal<TxPtr, M> {
+    #[inline]
+    fn from(seal: WitnessSeal<M>) -> Self {
+        BlindSeal::with_blinded_vout(seal.method, seal.vout, seal.blinding)
+    }
+}
+
+impl<M: SealCloseMethod> TryFrom<BlindSeal<TxPtr, M>> for WitnessSeal<M> {
+    type Error = ExplicitTxidError;
+
+    fn try_from(seal: BlindSeal<TxPtr, M>) -> Result<Self, Self::Error> {
+        match seal.txid {
+            TxPtr::WitnessTx => {
+                Ok(WitnessSeal::with_blinding(seal.method, seal.vout, seal.blinding))
+            }
+            TxPtr::Txid(txid) => Err(ExplicitTxidError(txid)),
+        }
+    }
+}
+
+impl<M: SealCloseMethod> TxoSeal<M> for WitnessSeal<M> {
+    #[inline]
+    fn method(&self) -> M { self.method }
+
+    #[inline]
+    fn txid(&self) -> Option<Txid> { None }
+
+    #[inline]
+    fn vout(&self) -> Vout { self.vout }
+
+    #[inline]
+    fn outpoint(&self) -> Option<Outpoint> { None }
+
+    #[inline]
+    fn txid_or(&self, default_txid: Txid) -> Txid { default_txid }
+
+    #[inline]
+    fn outpoint_or(&self, default_txid: Txid) -> Outpoint { Outpoint::new(default_txid, self.vout) }
+}
+
+impl<M: SealCloseMethod> WitnessSeal<M> {
+    /// Creates new seal pointing to an output of the witness transaction.
+    /// Uses `thread_rng` to initialize blinding factor.
+    #[inline]
+    pub fn new_random(method: M, vout: impl Into<Vout>) -> Self {
+        WitnessSeal::with_blinding(method, vout, thread_rng().next_u64())
+    }
+
+    /// Reconstructs previously defined seal pointing to an output of the
+    /// witness transaction with a given method, output number and previously
+    /// generated blinding factor value.
+    pub fn with_blinding(method: M, vout: impl Into<Vout>, blinding: u64) -> Self {
+        WitnessSeal {
+            method,
+            vout: vout.into(),
+            blinding,
+        }
+    }
+
+    /// Converts witness seal into a seal pointing to a transaction with known
+    /// id.
+    pub fn resolve(self, txid: Txid) -> BlindSeal<Txid, M> {
+        BlindSeal::with_blinding(self.method, txid, self.vout, self.blinding)
+    }
+}
+
+/// Errors constructing seal with [`BlindSealBuilder`].
+#[derive(Copy, Clone, PartialEq, Eq, Hash, Debug, Display, Error)]
+#[display(doc_comments)]
+pub enum SealBuildError {
+    /// seal close method is not specified.
+    NoMethod,
+
+    /// seal close method is specified more than once.
+    ConflictingMethod,
+
+    /// seal output is not specified; either an outpoint or a witness
+    /// transaction output number must be provided.
+    NoOutput,
+
+    /// both an outpoint and a witness transaction output are specified for the
+    /// seal.
+    ConflictingOutput,
+
+    /// seal blinding factor is not specified.
+    NoBlinding,
+
+    /// seal blinding factor is specified more than once.
+    ConflictingBlinding,
+}
+
+/// Builder constructing [`ChainBlindSeal`] and validating that each of the
+/// seal components is specified exactly once.
+#[derive(Clone, Debug)]
+pub struct BlindSealBuilder<M: SealCloseMethod = CloseMethod> {
+    method: Option<M>,
+    output: Option<(TxPtr, Vout)>,
+    blinding: Option<u64>,
+    error: Option<SealBuildError>,
+}
+
+impl<M: SealCloseMethod> Default for BlindSealBuilder<M> {
+    fn default() -> Self {
+        BlindSealBuilder {
+            method: None,
+            output: None,
+            blinding: None,
+            error: None,
+        }
+    }
+}
+
+impl<M: SealCloseMethod> BlindSealBuilder<M> {
+    /// Sets seal close method.
+    pub fn method(mut self, method: M) -> Self {
+        if self.method.replace(method).is_some() {
+            self.error.get_or_insert(SealBuildError::ConflictingMethod);
+        }
+        self
+    }
+
+    /// Sets outpoint defined by the seal.
+    pub fn outpoint(self, outpoint: Outpoint) -> Self {
+        self.output(TxPtr::Txid(outpoint.txid), outpoint.vout)
+    }
+
+    /// Makes the seal pointing to the output `vout` of the witness
+    /// transaction.
+    pub fn witness(self, vout: impl Into<Vout>) -> Self {
+        self.output(TxPtr::WitnessTx, vout.into())
+    }
+
+    /// Sets blinding factor of the seal.
+    pub fn blinding(mut self, blinding: u64) -> Self {
+        if self.blinding.replace(blinding).is_some() {
+            self.error.get_or_insert(SealBuildError::ConflictingBlinding);
+        }
+        self
+    }
+
+    /// Sets blinding factor of the seal to a random value generated with
+    /// `thread_rng`.
+    pub fn random_blinding(self) -> Self { self.blinding(thread_rng().next_u64()) }
+
+    /// Constructs the seal.
+    ///
+    /// # Errors
+    ///
+    /// If any of the seal components is missed or is specified more than once.
+    pub fn build(self) -> Result<ChainBlindSeal<M>, SealBuildError> {
+        if let Some(err) = self.error {
+            return Err(err);
+        }
+        let method = self.method.ok_or(SealBuildError::NoMethod)?;
+        let (txid, vout) = self.output.ok_or(SealBuildError::NoOutput)?;
+        let blinding = self.blinding.ok_or(SealBuildError::NoBlinding)?;
+        Ok(BlindSeal::with_blinding(method, txid, vout, blinding))
+    }
+
+    fn output(mut self, txid: TxPtr, vout: Vout) -> Self {
+        if self.output.replace((txid, vout)).is_some() {
+            self.error.get_or_insert(SealBuildError::ConflictingOutput);
+        }
+        self
+    }
+}
+
 /// Errors happening during parsing string representation of different forms of
 /// single-use-seals
 #[derive(Clone, PartialEq, Eq, Debug, Display, Error, From)]
@@ -285,6 +636,12 @@ pub enum ParseError {
     NonHexBlinding,
 }
 
+impl OutpointParseError for ParseError {
+    fn wrong_txid(err: hex::Error) -> Self { ParseError::WrongTxid(err) }
+
+    fn wrong_vout() -> Self { ParseError::WrongVout }
+}
+
 impl<Id: SealTxid, M: SealCloseMethod> FromStr for BlindSeal<Id, M>
 where M: FromStr<Err = MethodParseError>
 {
@@ -301,23 +658,41 @@ where M: FromStr<Err = MethodParseError>
             }
             (Some(method), Some(txid), Some(vout), Some(blinding), None) => Ok(BlindSeal {
                 method: method.parse()?,
-                blinding: u64::from_str_radix(blinding.trim_start_matches("0x"), 16)
-                    .map_err(|_| ParseError::WrongBlinding)?,
-                txid: Id::from_str(txid).map_err(ParseError::WrongTxid)?,
-                vout: vout.parse().map_err(|_| ParseError::WrongVout)?,
+                blinding: Self::parse_blinding_hex(blinding)?,
+                txid: parse_txid(txid)?,
+                vout: parse_vout(vout)?,
             }),
             _ => Err(ParseError::WrongStructure),
         }
     }
 }
 
+impl<Id: SealTxid, M: SealCloseMethod> BlindSeal<Id, M>
+where M: FromStr<Err = MethodParseError>
+{
+    /// Parses seal definition, allowing the blinding factor to be omitted, as
+    /// was done by some legacy software: `method:txid:vout` is parsed as a seal
+    /// with zero blinding factor. Returns the seal together with a flag
+    /// indicating whether the blinding factor was defaulted, such that the
+    /// caller may warn the user about the use of a legacy seal.
+    ///
+    /// The standard [`FromStr`] implementation always requires the blinding
+    /// factor.
+    pub fn from_str_lenient(s: &str) -> Result<(Self, bool), ParseError> {
+        if !s.contains('#') && s.split(':').count() == 3 {
+            return Self::from_str(&format!("{s}#0x0")).map(|seal| (seal, true));
+        }
+        Self::from_str(s).map(|seal| (seal, false))
+    }
+}
+
 impl<Id: SealTxid, M: SealCloseMethod> Display for BlindSeal<Id, M>
 where
     Self: TxoSeal<M>,
     M: Display,
 {
     fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
-        write!(f, "{}:{}:{}#{:#010x}", self.method, self.txid, self.vout, self.blinding)
+        write!(f, "{}:{}:{}#{}", self.method, self.txid, self.vout, self.blinding_hex())
     }
 }
 
@@ -325,6 +700,180 @@ where
 mod test {
     use super::*;
 
+    #[test]
+    fn from_str_lenient() {
+        let txid = "646ca5c1062619e2a2d60771c9dfd820551fb773e4dc8c4ed67965a8d1fae839";
+        let legacy = format!("tapret1st:{txid}:1");
+        assert_eq!(
+            ChainBlindSeal::<CloseMethod>::from_str(&legacy),
+            Err(ParseError::WrongStructure)
+        );
+        let (seal, defaulted) = ChainBlindSeal::<CloseMethod>::from_str_lenient(&legacy).unwrap();
+        assert!(defaulted);
+        assert_eq!(seal, ChainBlindSeal::with_blinding(
+            CloseMethod::TapretFirst,
+            TxPtr::Txid(Txid::from_str(txid).unwrap()),
+            1u32,
+            0
+        ));
+
+        let full = format!("tapret1st:{txid}:1#0x78ca95");
+        let (seal, defaulted) = ChainBlindSeal::<CloseMethod>::from_str_lenient(&full).unwrap();
+        assert!(!defaulted);
+        assert_eq!(Ok(seal), ChainBlindSeal::<CloseMethod>::from_str(&full));
+
+        // other errors are not masked
+        assert_eq!(
+            ChainBlindSeal::<CloseMethod>::from_str_lenient(&format!("tapret1st:{txid}:x")),
+            Err(ParseError::WrongVout)
+        );
+        assert_eq!(
+            ChainBlindSeal::<CloseMethod>::from_str_lenient(&format!("tapret1st:{txid}")),
+            Err(ParseError::BlindingRequired)
+        );
+    }
+
+    #[test]
+    fn outpoint_method() {
+        let outpoint = Outpoint::new(
+            Txid::from_str("646ca5c1062619e2a2d60771c9dfd820551fb773e4dc8c4ed67965a8d1fae839")
+                .unwrap(),
+            21u32,
+        );
+        assert_eq!(SingleBlindSeal::default_method(), CloseMethod::TapretFirst);
+        let seal = SingleBlindSeal::tapret_first_rand_from(outpoint);
+        assert_eq!(seal.method, SingleBlindSeal::default_method());
+        assert_eq!(Outpoint::from(seal), outpoint);
+
+        let seal = SingleBlindSeal::from_outpoint_method(outpoint, CloseMethod::OpretFirst);
+        assert_eq!(seal.method, CloseMethod::OpretFirst);
+        assert_eq!(Outpoint::from(seal), outpoint);
+
+        let seal = ChainBlindSeal::from_outpoint_method(outpoint, CloseMethod::OpretFirst);
+        assert_eq!(seal.method, CloseMethod::OpretFirst);
+        assert_eq!(seal.outpoint(), Some(outpoint));
+    }
+
+    #[test]
+    fn compact_bytes() {
+        let txid =
+            Txid::from_str("646ca5c1062619e2a2d60771c9dfd820551fb773e4dc8c4ed67965a8d1fae839")
+                .unwrap();
+        let seals = [
+            (ChainBlindSeal::with_blinded_vout(CloseMethod::TapretFirst, 2u32, 0xdead), 10),
+            (ChainBlindSeal::with_blinded_vout(CloseMethod::OpretFirst, 1000u32, 0xdead), 13),
+            (ChainBlindSeal::with_blinding(CloseMethod::OpretFirst, txid, 21u32, 0xdead), 42),
+            (ChainBlindSeal::with_blinding(CloseMethod::TapretFirst, txid, u32::MAX, 0xdead), 45),
+        ];
+        for (seal, len) in seals {
+            let data = seal.to_compact_bytes();
+            assert_eq!(data.len(), len);
+            assert_eq!(ChainBlindSeal::from_compact_bytes(&data), Ok(seal));
+        }
+
+        let seal = ChainBlindSeal::with_blinded_vout(CloseMethod::TapretFirst, 2u32, 0xdead);
+        let data = seal.to_compact_bytes();
+        assert_eq!(data[..2], [0x41, 0x02]);
+        assert_eq!(data[2..], 0xdead_u64.to_le_bytes());
+
+        assert_eq!(
+            ChainBlindSeal::from_compact_bytes(&[]),
+            Err(CompactSealError::InvalidLength(0))
+        );
+        assert_eq!(
+            ChainBlindSeal::from_compact_bytes(&data[..9]),
+            Err(CompactSealError::InvalidLength(9))
+        );
+        let mut wrong = data.clone();
+        wrong[0] = 0x45;
+        assert_eq!(
+            ChainBlindSeal::from_compact_bytes(&wrong),
+            Err(CompactSealError::UnknownMethod(5))
+        );
+        let mut wrong = vec![0x01, 0x02, 0x00, 0x00, 0x00];
+        wrong.extend(0xdead_u64.to_le_bytes());
+        assert_eq!(
+            ChainBlindSeal::from_compact_bytes(&wrong),
+            Err(CompactSealError::NonCanonicalVout(2))
+        );
+    }
+
+    #[test]
+    fn builder() {
+        let outpoint = Outpoint::new(
+            Txid::from_str("646ca5c1062619e2a2d60771c9dfd820551fb773e4dc8c4ed67965a8d1fae839")
+                .unwrap(),
+            21u32,
+        );
+        let seal = ChainBlindSeal::builder()
+            .method(CloseMethod::OpretFirst)
+            .outpoint(outpoint)
+            .blinding(0xdead)
+            .build();
+        assert_eq!(
+            seal,
+            Ok(ChainBlindSeal::with_blinding(CloseMethod::OpretFirst, outpoint.txid, 21u32, 0xdead))
+        );
+
+        let seal = ChainBlindSeal::builder()
+            .method(CloseMethod::TapretFirst)
+            .witness(2u32)
+            .random_blinding()
+            .build()
+            .unwrap();
+        assert_eq!(seal.txid, TxPtr::WitnessTx);
+        assert_eq!(seal.vout, Vout::from(2u32));
+
+        let builder = ChainBlindSeal::<CloseMethod>::builder();
+        assert_eq!(builder.clone().build(), Err(SealBuildError::NoMethod));
+        let builder = builder.method(CloseMethod::TapretFirst);
+        assert_eq!(builder.clone().build(), Err(SealBuildError::NoOutput));
+        let builder = builder.witness(2u32);
+        assert_eq!(builder.clone().build(), Err(SealBuildError::NoBlinding));
+        assert_eq!(
+            builder.clone().outpoint(outpoint).blinding(0xdead).build(),
+            Err(SealBuildError::ConflictingOutput)
+        );
+        assert_eq!(
+            builder.clone().blinding(0xdead).random_blinding().build(),
+            Err(SealBuildError::ConflictingBlinding)
+        );
+        assert_eq!(
+            builder
+                .clone()
+                .method(CloseMethod::OpretFirst)
+                .blinding(0xdead)
+                .build(),
+            Err(SealBuildError::ConflictingMethod)
+        );
+        assert_eq!(
+            builder
+                .method(CloseMethod::TapretFirst)
+                .blinding(0xdead)
+                .build(),
+            Err(SealBuildError::ConflictingMethod)
+        );
+    }
+
+    #[test]
+    fn fingerprint() {
+        let mut seal = ChainBlindSeal::with_blinding(
+            CloseMethod::TapretFirst,
+            TxPtr::Txid(
+                Txid::from_str("646ca5c1062619e2a2d60771c9dfd820551fb773e4dc8c4ed67965a8d1fae839")
+                    .unwrap(),
+            ),
+            21,
+            0x31bbed7e7b2d,
+        );
+        assert_eq!(seal.fingerprint(), "tapret1st:646ca5c1…:21");
+        seal.blinding = 0xdead;
+        assert_eq!(seal.fingerprint(), "tapret1st:646ca5c1…:21");
+
+        seal.txid = TxPtr::WitnessTx;
+        assert_eq!(seal.fingerprint(), "tapret1st:~:21");
+    }
+
     #[test]
     fn blind_seal_str() {
         let mut outpoint_reveal = BlindSeal {
@@ -341,16 +890,38 @@ mod test {
         assert_eq!(
             &s,
             "tapret1st:646ca5c1062619e2a2d60771c9dfd820551fb773e4dc8c4ed67965a8d1fae839:21#\
-             0x31bbed7e7b2d"
+             0x000031bbed7e7b2d"
         );
         // round-trip
         assert_eq!(ChainBlindSeal::from_str(&s).unwrap(), outpoint_reveal);
 
         outpoint_reveal.txid = TxPtr::WitnessTx;
         let s = outpoint_reveal.to_string();
-        assert_eq!(&s, "tapret1st:~:21#0x31bbed7e7b2d");
+        assert_eq!(&s, "tapret1st:~:21#0x000031bbed7e7b2d");
+        // round-trip
+        assert_eq!(ChainBlindSeal::from_str(&s).unwrap(), outpoint_reveal);
+
+        // large blinding keeps full width
+        outpoint_reveal.blinding = 0xfedcba9876543210;
+        assert_eq!(outpoint_reveal.blinding_hex(), "0xfedcba9876543210");
+        let s = outpoint_reveal.to_string();
+        assert_eq!(&s, "tapret1st:~:21#0xfedcba9876543210");
+        assert_eq!(ChainBlindSeal::from_str(&s).unwrap(), outpoint_reveal);
+        assert_eq!(
+            ChainBlindSeal::<CloseMethod>::parse_blinding_hex("0x0xfe"),
+            Err(ParseError::WrongBlinding)
+        );
+        assert_eq!(
+            ChainBlindSeal::<CloseMethod>::parse_blinding_hex("0x1fedcba9876543210"),
+            Err(ParseError::WrongBlinding)
+        );
         // round-trip
         assert_eq!(ChainBlindSeal::from_str(&s).unwrap(), outpoint_reveal);
+        let err = Outpoint::try_from(outpoint_reveal).unwrap_err();
+        assert_eq!(err, WitnessVoutError::for_seal(&outpoint_reveal));
+        assert_eq!(err.vout, Vout::from(21));
+        assert_eq!(err.method, CloseMethod::TapretFirst);
+        assert!(err.to_string().contains("output 21"));
 
         // wrong method
         assert_eq!(
@@ -478,4 +1049,59 @@ mod test {
             Err(ParseError::MethodRequired)
         );
     }
+
+    #[test]
+    fn witness_seal() {
+        let seal = WitnessSeal::with_blinding(CloseMethod::OpretFirst, 2u32, 0x31bbed7e7b2d);
+        let blind_seal =
+            ChainBlindSeal::with_blinded_vout(CloseMethod::OpretFirst, 2u32, 0x31bbed7e7b2d);
+
+        assert_eq!(seal.txid(), None);
+        assert_eq!(seal.outpoint(), None);
+        assert_eq!(seal.conceal(), blind_seal.conceal());
+        assert_eq!(ChainBlindSeal::from(seal), blind_seal);
+        assert_eq!(WitnessSeal::try_from(blind_seal), Ok(seal));
+
+        let txid =
+            Txid::from_str("646ca5c1062619e2a2d60771c9dfd820551fb773e4dc8c4ed67965a8d1fae839")
+                .unwrap();
+        assert_eq!(seal.resolve(txid), blind_seal.resolve(txid));
+        assert_eq!(
+            WitnessSeal::try_from(ChainBlindSeal::with_blinding(
+                CloseMethod::OpretFirst,
+                txid,
+                2u32,
+                0x31bbed7e7b2d
+            )),
+            Err(ExplicitTxidError(txid))
+        );
+    }
+
+    #[test]
+    fn blinding_display_width() {
+        let seal =
+            |blinding| ChainBlindSeal::with_blinded_vout(CloseMethod::OpretFirst, 0u32, blinding);
+        let small = seal(0xabcd);
+        let large = seal(0x1234_5678_9abc);
+        assert_eq!(small.to_string(), "opret1st:~:0#0x000000000000abcd");
+        assert_eq!(large.to_string(), "opret1st:~:0#0x0000123456789abc");
+        // equal width makes string ordering match numeric ordering of blindings
+        assert!(small.blinding_hex() < large.blinding_hex());
+        assert_eq!(seal(0).blinding_hex().len(), seal(u64::MAX).blinding_hex().len());
+    }
+
+    #[test]
+    fn witness_zero_txid_conceal() {
+        let zero_txid = Txid::coinbase();
+        let witness = ChainBlindSeal::with_blinded_vout(CloseMethod::TapretFirst, 1u32, 0xdead);
+        let zero =
+            ChainBlindSeal::with_blinding(CloseMethod::TapretFirst, zero_txid, 1u32, 0xdead);
+        let single_zero =
+            SingleBlindSeal::with_blinding(CloseMethod::TapretFirst, zero_txid, 1u32, 0xdead);
+
+        assert_ne!(witness.conceal(), zero.conceal());
+        assert_ne!(witness.conceal(), single_zero.conceal());
+        assert_eq!(zero.txid(), Some(zero_txid));
+        assert_eq!(witness.txid(), None);
+    }
 }
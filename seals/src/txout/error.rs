@@ -21,7 +21,11 @@
 
 use std::error::Error;
 
-use bc::Outpoint;
+use bc::{Outpoint, Txid, Vout};
+use commit_verify::mpc;
+
+use crate::txout::{CloseMethod, TxoSeal};
+use crate::SealCloseMethod;
 
 /// Seal verification errors.
 #[derive(Clone, PartialEq, Eq, Debug, Display, From, Error)]
@@ -35,6 +39,10 @@ pub enum VerifyError<E: Error> {
     /// seals provided for a batch verification have inconsistent close method.
     InconsistentCloseMethod,
 
+    /// seal close method doesn't match the method of the deterministic bitcoin
+    /// commitment in the witness.
+    CloseMethodMismatch,
+
     /// the provided witness transaction does not closes seal {0}.
     WitnessNotClosingSeal(Outpoint),
 
@@ -46,10 +54,51 @@ pub enum VerifyError<E: Error> {
     Dbc(E),
 }
 
+/// Errors verifying that a seal is closed over a message using an anchor (see
+/// [`super::verify_seal_closing`]).
+#[derive(Clone, PartialEq, Eq, Debug, Display, From, Error)]
+#[display(doc_comments)]
+pub enum ClosingError<E: Error> {
+    /// anchor DBC proof doesn't match the proof from the seal witness.
+    AnchorMismatch,
+
+    /// anchor doesn't commit to the message. Details: {0}
+    #[from]
+    Mpc(mpc::InvalidProof),
+
+    /// seal is not closed by the witness.
+    #[display(inner)]
+    #[from]
+    Seal(VerifyError<E>),
+}
+
 /// Error happening if the seal data holds only witness transaction output
 /// number and thus can't be used alone for constructing full bitcoin
 /// transaction output data which must include the witness transaction id
 /// (unknown to the seal).
 #[derive(Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Debug, Display, Error)]
-#[display("witness txid is unknown; unable to reconstruct full outpoint data")]
-pub struct WitnessVoutError;
+#[display("witness txid of the seal at output {vout} is unknown; unable to construct outpoint")]
+pub struct WitnessVoutError<M: SealCloseMethod = CloseMethod> {
+    /// Close method of the seal.
+    pub method: M,
+
+    /// Witness transaction output number of the seal.
+    pub vout: Vout,
+}
+
+impl<M: SealCloseMethod> WitnessVoutError<M> {
+    /// Constructs error for the seal lacking witness txid.
+    #[inline]
+    pub fn for_seal(seal: &impl TxoSeal<M>) -> Self {
+        WitnessVoutError {
+            method: seal.method(),
+            vout: seal.vout(),
+        }
+    }
+}
+
+/// seal definition contains explicit transaction id {0} and can't be used as
+/// a witness seal.
+#[derive(Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Debug, Display, Error)]
+#[display(doc_comments)]
+pub struct ExplicitTxidError(pub Txid);
@@ -28,7 +28,7 @@ use amplify::hex;
 use bc::{Outpoint, Txid, Vout};
 use dbc::MethodParseError;
 
-use crate::txout::seal::{SealTxid, TxPtr};
+use crate::txout::seal::{parse_txid, parse_vout, OutpointParseError, SealTxid, TxPtr};
 use crate::txout::{CloseMethod, TxoSeal, WitnessVoutError};
 use crate::SealCloseMethod;
 
@@ -60,19 +60,19 @@ pub struct ExplicitSeal<Id: SealTxid, M: SealCloseMethod = CloseMethod> {
 }
 
 impl<M: SealCloseMethod> TryFrom<&ExplicitSeal<TxPtr, M>> for Outpoint {
-    type Error = WitnessVoutError;
+    type Error = WitnessVoutError<M>;
 
     #[inline]
     fn try_from(reveal: &ExplicitSeal<TxPtr, M>) -> Result<Self, Self::Error> {
         reveal
             .txid
             .map_to_outpoint(reveal.vout)
-            .ok_or(WitnessVoutError)
+            .ok_or_else(|| WitnessVoutError::for_seal(reveal))
     }
 }
 
 impl<M: SealCloseMethod> TryFrom<ExplicitSeal<TxPtr, M>> for Outpoint {
-    type Error = WitnessVoutError;
+    type Error = WitnessVoutError<M>;
 
     #[inline]
     fn try_from(reveal: ExplicitSeal<TxPtr, M>) -> Result<Self, Self::Error> {
@@ -112,6 +112,18 @@ impl<Id: SealTxid, M: SealCloseMethod> TxoSeal<M> for ExplicitSeal<Id, M> {
 
 impl<Id: SealTxid, M: SealCloseMethod> ExplicitSeal<Id, M> {
     /// Constructs seal for the provided outpoint and seal closing method.
+    ///
+    /// ```
+    /// # use std::str::FromStr;
+    /// # use bc::{Outpoint, Txid};
+    /// # use seals::txout::{CloseMethod, ExplicitSeal, TxoSeal};
+    /// let txid =
+    ///     Txid::from_str("646ca5c1062619e2a2d60771c9dfd820551fb773e4dc8c4ed67965a8d1fae839")
+    ///         .unwrap();
+    /// let outpoint = Outpoint::new(txid, 2u32);
+    /// let seal = ExplicitSeal::<Txid>::new(CloseMethod::OpretFirst, outpoint);
+    /// assert_eq!(seal.outpoint(), Some(outpoint));
+    /// ```
     #[inline]
     pub fn new(method: M, outpoint: Outpoint) -> ExplicitSeal<Id, M> {
         Self {
@@ -122,6 +134,13 @@ impl<Id: SealTxid, M: SealCloseMethod> ExplicitSeal<Id, M> {
     }
 
     /// Constructs seal.
+    ///
+    /// ```
+    /// # use seals::txout::{CloseMethod, ExplicitSeal, TxPtr, TxoSeal};
+    /// let seal = ExplicitSeal::with(CloseMethod::TapretFirst, TxPtr::WitnessTx, 1u32);
+    /// assert_eq!(seal.method(), CloseMethod::TapretFirst);
+    /// assert_eq!(seal.vout().into_u32(), 1);
+    /// ```
     #[inline]
     pub fn with(method: M, txid: Id, vout: impl Into<Vout>) -> ExplicitSeal<Id, M> {
         ExplicitSeal {
@@ -132,6 +151,22 @@ impl<Id: SealTxid, M: SealCloseMethod> ExplicitSeal<Id, M> {
     }
 }
 
+impl<M: SealCloseMethod> ExplicitSeal<TxPtr, M> {
+    /// Constructs seal pointing to an output of the witness transaction, which
+    /// id is not known yet.
+    ///
+    /// ```
+    /// # use seals::txout::{CloseMethod, ExplicitSeal, TxPtr, TxoSeal};
+    /// let seal = ExplicitSeal::<TxPtr>::witness(CloseMethod::TapretFirst, 1u32);
+    /// assert_eq!(seal.txid(), None);
+    /// assert_eq!(seal.to_string(), "tapret1st:~:1");
+    /// ```
+    #[inline]
+    pub fn witness(method: M, vout: impl Into<Vout>) -> Self {
+        Self::with(method, TxPtr::WitnessTx, vout)
+    }
+}
+
 impl<M: SealCloseMethod> ExplicitSeal<Txid, M> {
     /// Converts seal into a transaction outpoint.
     #[inline]
@@ -166,6 +201,12 @@ pub enum ParseError {
     WrongStructure,
 }
 
+impl OutpointParseError for ParseError {
+    fn wrong_txid(err: hex::Error) -> Self { ParseError::WrongTxid(err) }
+
+    fn wrong_vout() -> Self { ParseError::WrongVout }
+}
+
 impl<Id: SealTxid, M: SealCloseMethod> FromStr for ExplicitSeal<Id, M>
 where M: FromStr<Err = MethodParseError>
 {
@@ -178,8 +219,8 @@ where M: FromStr<Err = MethodParseError>
             (Some(_), Some(""), ..) => Err(ParseError::TxidRequired),
             (Some(method), Some(txid), Some(vout), None) => Ok(ExplicitSeal {
                 method: method.parse()?,
-                txid: Id::from_str(txid).map_err(ParseError::WrongTxid)?,
-                vout: vout.parse().map_err(|_| ParseError::WrongVout)?,
+                txid: parse_txid(txid)?,
+                vout: parse_vout(vout)?,
             }),
             _ => Err(ParseError::WrongStructure),
         }
@@ -193,3 +234,53 @@ where M: Display
         write!(f, "{}:{}:{}", self.method, self.txid, self.vout,)
     }
 }
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn txid() -> Txid {
+        Txid::from_str("646ca5c1062619e2a2d60771c9dfd820551fb773e4dc8c4ed67965a8d1fae839").unwrap()
+    }
+
+    #[test]
+    fn construction() {
+        let outpoint = Outpoint::new(txid(), 2u32);
+
+        let seal = ExplicitSeal::<Txid>::new(CloseMethod::OpretFirst, outpoint);
+        assert_eq!(seal.method(), CloseMethod::OpretFirst);
+        assert_eq!(seal.txid(), Some(txid()));
+        assert_eq!(seal.vout(), Vout::from(2));
+        assert_eq!(seal.to_outpoint(), outpoint);
+        assert_eq!(Outpoint::from(seal), outpoint);
+
+        let seal = ExplicitSeal::<TxPtr>::new(CloseMethod::TapretFirst, outpoint);
+        assert_eq!(seal.txid, TxPtr::Txid(txid()));
+        assert_eq!(seal.outpoint(), Some(outpoint));
+        assert_eq!(Outpoint::try_from(seal), Ok(outpoint));
+
+        let seal = ExplicitSeal::with(CloseMethod::TapretFirst, TxPtr::Txid(txid()), 2u32);
+        assert_eq!(seal, ExplicitSeal::<TxPtr>::new(CloseMethod::TapretFirst, outpoint));
+
+        let seal = ExplicitSeal::<TxPtr>::witness(CloseMethod::TapretFirst, 2u32);
+        assert_eq!(seal, ExplicitSeal::with(CloseMethod::TapretFirst, TxPtr::WitnessTx, 2u32));
+        assert_eq!(seal.txid(), None);
+        assert_eq!(seal.outpoint(), None);
+        assert_eq!(seal.outpoint_or(txid()), outpoint);
+        assert_eq!(Outpoint::try_from(seal), Err(WitnessVoutError::for_seal(&seal)));
+    }
+
+    #[test]
+    fn string_roundtrip() {
+        let seal = ExplicitSeal::<TxPtr>::witness(CloseMethod::TapretFirst, 2u32);
+        assert_eq!(seal.to_string(), "tapret1st:~:2");
+        assert_eq!(ExplicitSeal::from_str("tapret1st:~:2"), Ok(seal));
+
+        let seal = ExplicitSeal::<Txid>::new(CloseMethod::OpretFirst, Outpoint::new(txid(), 2u32));
+        assert_eq!(ExplicitSeal::from_str(&seal.to_string()), Ok(seal));
+        assert!(matches!(
+            ExplicitSeal::<Txid>::from_str("opret1st:~:2"),
+            Err(ParseError::WrongTxid(_))
+        ));
+    }
+}
@@ -26,10 +26,17 @@ pub mod blind;
 mod error;
 pub mod explicit;
 mod seal;
+mod verifier;
+pub mod wide;
 mod witness;
 
-pub use blind::{BlindSeal, ChainBlindSeal, SingleBlindSeal};
-pub use error::{VerifyError, WitnessVoutError};
+pub use blind::{
+    BlindSeal, BlindSealBuilder, ChainBlindSeal, CompactSealError, SealBuildError, SingleBlindSeal,
+    WitnessSeal,
+};
+pub use error::{ClosingError, ExplicitTxidError, VerifyError, WitnessVoutError};
 pub use explicit::ExplicitSeal;
 pub use seal::{CloseMethod, SealTxid, TxPtr, TxoSeal};
-pub use witness::Witness;
+pub use verifier::SealVerifier;
+pub use wide::WideBlindSeal;
+pub use witness::{verify_seal_closing, ClosedSeal, Witness};
@@ -148,3 +148,60 @@ impl FromStr for TxPtr {
         }
     }
 }
+
+/// Errors of seal string representation parsing which can be reported by
+/// [`parse_txid`] and [`parse_vout`].
+pub(crate) trait OutpointParseError {
+    /// Error for the transaction id part of the seal which is not a valid
+    /// hexadecimal txid.
+    fn wrong_txid(err: hex::Error) -> Self;
+
+    /// Error for the output number part of the seal which is not a decimal
+    /// unsigned integer.
+    fn wrong_vout() -> Self;
+}
+
+/// Parses transaction id part of a seal string representation.
+pub(crate) fn parse_txid<Id: SealTxid, E: OutpointParseError>(s: &str) -> Result<Id, E> {
+    Id::from_str(s).map_err(E::wrong_txid)
+}
+
+/// Parses output number part of a seal string representation, which must be a
+/// decimal unsigned integer.
+pub(crate) fn parse_vout<E: OutpointParseError>(s: &str) -> Result<Vout, E> {
+    Vout::from_str(s).map_err(|_| E::wrong_vout())
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::txout::{blind, explicit};
+
+    #[test]
+    fn parse_outpoint_parts() {
+        let txid = "646ca5c1062619e2a2d60771c9dfd820551fb773e4dc8c4ed67965a8d1fae839";
+        assert_eq!(parse_txid::<Txid, blind::ParseError>(txid), Ok(Txid::from_str(txid).unwrap()));
+        assert_eq!(parse_txid::<TxPtr, blind::ParseError>("~"), Ok(TxPtr::WitnessTx));
+        assert_eq!(parse_vout::<blind::ParseError>("5"), Ok(Vout::from(5)));
+
+        assert_eq!(parse_vout::<blind::ParseError>("-5"), Err(blind::ParseError::WrongVout));
+        assert_eq!(parse_vout::<blind::ParseError>("i9"), Err(blind::ParseError::WrongVout));
+        assert_eq!(parse_vout::<blind::ParseError>("0x765"), Err(blind::ParseError::WrongVout));
+        assert_eq!(
+            parse_txid::<Txid, blind::ParseError>("rvgbdg"),
+            Err(blind::ParseError::WrongTxid(hex::Error::InvalidChar(b'r')))
+        );
+        assert_eq!(
+            parse_txid::<TxPtr, blind::ParseError>("~~"),
+            Err(blind::ParseError::WrongTxid(hex::Error::OddLengthString(2)))
+        );
+
+        // both seal types report the same errors
+        assert_eq!(parse_vout::<explicit::ParseError>("-5"), Err(explicit::ParseError::WrongVout));
+        assert_eq!(parse_vout::<explicit::ParseError>("i9"), Err(explicit::ParseError::WrongVout));
+        assert_eq!(
+            parse_txid::<Txid, explicit::ParseError>("rvgbdg"),
+            Err(explicit::ParseError::WrongTxid(hex::Error::InvalidChar(b'r')))
+        );
+    }
+}
@@ -0,0 +1,162 @@
+// Bitcoin protocol single-use-seals library.
+//
+// SPDX-License-Identifier: Apache-2.0
+//
+// Written in 2019-2024 by
+//     Dr Maxim Orlovsky <orlovsky@lnp-bp.org>
+//
+// Copyright (C) 2019-2024 LNP/BP Standards Association. All rights reserved.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use std::collections::BTreeMap;
+use std::convert::Infallible;
+
+use bc::{Outpoint, Tx, Txid};
+
+use crate::txout::{TxoSeal, VerifyError};
+use crate::SealCloseMethod;
+
+/// Verifier checking that large sets of seals are closed by some of the
+/// witness transactions from a batch.
+///
+/// The verifier indexes all outpoints spent by the witness transactions once,
+/// such that each seal is verified with a single lookup instead of scanning
+/// inputs of all transactions.
+///
+/// The verifier checks only that the seals are spent by the witness
+/// transactions; verification of the deterministic bitcoin commitments inside
+/// them must be done with [`super::Witness`].
+#[derive(Clone, Eq, PartialEq, Debug, Default)]
+pub struct SealVerifier {
+    spenders: BTreeMap<Outpoint, Txid>,
+}
+
+impl SealVerifier {
+    /// Constructs verifier indexing provided witness transactions.
+    pub fn with<'tx>(txs: impl IntoIterator<Item = &'tx Tx>) -> Self {
+        let mut verifier = SealVerifier::default();
+        for tx in txs {
+            verifier.add_tx(tx);
+        }
+        verifier
+    }
+
+    /// Adds witness transaction to the index.
+    ///
+    /// If some of the transaction inputs are already spent by other indexed
+    /// transaction (which is impossible for the transactions mined in the same
+    /// chain), the first transaction is kept as the spender.
+    pub fn add_tx(&mut self, tx: &Tx) {
+        let txid = tx.txid();
+        for txin in &tx.inputs {
+            self.spenders.entry(txin.prev_output).or_insert(txid);
+        }
+    }
+
+    /// Returns id of the indexed transaction spending the outpoint, if any.
+    #[inline]
+    pub fn spending_txid(&self, outpoint: Outpoint) -> Option<Txid> {
+        self.spenders.get(&outpoint).copied()
+    }
+
+    /// Verifies that the seal is closed by one of the indexed witness
+    /// transactions, returning id of that transaction.
+    ///
+    /// # Errors
+    ///
+    /// - [`VerifyError::NoWitnessTxid`] if the seal doesn't define the txid;
+    /// - [`VerifyError::WitnessNotClosingSeal`] if none of the indexed
+    ///   transactions spend the seal.
+    pub fn verify_seal<M: SealCloseMethod>(
+        &self,
+        seal: &impl TxoSeal<M>,
+    ) -> Result<Txid, VerifyError<Infallible>> {
+        let outpoint = seal.outpoint().ok_or(VerifyError::NoWitnessTxid)?;
+        self.spending_txid(outpoint)
+            .ok_or(VerifyError::WitnessNotClosingSeal(outpoint))
+    }
+
+    /// Verifies each of the seals with [`Self::verify_seal`], lazily returning
+    /// each seal together with its verification result.
+    pub fn verify_all<'verifier, Seal, M>(
+        &'verifier self,
+        seals: impl IntoIterator<Item = Seal> + 'verifier,
+    ) -> impl Iterator<Item = (Seal, Result<Txid, VerifyError<Infallible>>)> + 'verifier
+    where
+        Seal: TxoSeal<M> + 'verifier,
+        M: SealCloseMethod,
+    {
+        seals.into_iter().map(|seal| {
+            let res = self.verify_seal(&seal);
+            (seal, res)
+        })
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use bc::{LockTime, SeqNo, TxIn, TxVer, VarIntArray, Vout};
+
+    use super::*;
+    use crate::txout::{BlindSeal, CloseMethod, TxPtr};
+
+    fn tx_spending(outpoints: impl IntoIterator<Item = Outpoint>) -> Tx {
+        let inputs = outpoints
+            .into_iter()
+            .map(|prev_output| TxIn {
+                prev_output,
+                sig_script: none!(),
+                sequence: SeqNo::from_consensus_u32(0xFFFFFFFF),
+                witness: none!(),
+            })
+            .collect::<Vec<_>>();
+        Tx {
+            version: TxVer::V2,
+            inputs: VarIntArray::try_from(inputs).unwrap(),
+            outputs: none!(),
+            lock_time: LockTime::ZERO,
+        }
+    }
+
+    #[test]
+    fn overlapping_seals() {
+        let prev_txid = Txid::from([0xA5; 32]);
+        let tx1 = tx_spending([Outpoint::new(prev_txid, 0), Outpoint::new(prev_txid, 1)]);
+        let tx2 = tx_spending([Outpoint::new(prev_txid, 2)]);
+        let verifier = SealVerifier::with([&tx1, &tx2]);
+
+        let seal = |vout: u32, blinding: u64| {
+            BlindSeal::<TxPtr>::with_blinding(CloseMethod::OpretFirst, prev_txid, vout, blinding)
+        };
+        let witness_seal = BlindSeal::<TxPtr>::with_blinding(
+            CloseMethod::TapretFirst,
+            TxPtr::WitnessTx,
+            0u32,
+            4,
+        );
+        let seals = [seal(0, 1), seal(0, 2), seal(2, 3), seal(3, 4), witness_seal];
+
+        let results = verifier
+            .verify_all(seals)
+            .map(|(_, res)| res)
+            .collect::<Vec<_>>();
+        assert_eq!(results, vec![
+            Ok(tx1.txid()),
+            Ok(tx1.txid()),
+            Ok(tx2.txid()),
+            Err(VerifyError::WitnessNotClosingSeal(Outpoint::new(prev_txid, Vout::from(3)))),
+            Err(VerifyError::NoWitnessTxid),
+        ]);
+    }
+}
@@ -0,0 +1,227 @@
+// Bitcoin protocol single-use-seals library.
+//
+// SPDX-License-Identifier: Apache-2.0
+//
+// Written in 2019-2024 by
+//     Dr Maxim Orlovsky <orlovsky@lnp-bp.org>
+//
+// Copyright (C) 2019-2024 LNP/BP Standards Association. All rights reserved.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Blind seals with 128-bit blinding factor.
+//!
+//! [`WideBlindSeal`] is an opt-in alternative to [`BlindSeal`] for
+//! privacy-sensitive applications. Its concealed form is a [`SecretSeal`]
+//! produced by version 2 of the concealment procedure (see
+//! [`SecretSeal::commit_v2`]), committing to the txid, vout and blinding
+//! factor with a commitment tag specific to the seal close method
+//! ([`WideBlindSeal::tag`]). Thus it is NOT compatible with the standard
+//! concealment: a wide seal never conceals to the same value as a
+//! [`BlindSeal`], even if the lower 64 bits of its blinding factor match, and
+//! verifiers must know which seal version was used. The standard 64-bit
+//! [`BlindSeal`] remains the default seal type and is not affected.
+
+use bc::{Outpoint, TaggedHasher, Txid, Vout};
+use commit_verify::{Conceal, Sha256};
+use rand::{thread_rng, RngCore};
+
+use super::{BlindSeal, CloseMethod};
+use crate::txout::{SealTxid, TxoSeal};
+use crate::{SealCloseMethod, SecretSeal};
+
+/// Revealed seal definition with 128-bit blinding factor.
+///
+/// See the [module-level documentation](self) for the compatibility notes.
+#[derive(Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Debug)]
+#[derive(StrictType, StrictDumb, StrictEncode, StrictDecode)]
+#[strict_type(lib = dbc::LIB_NAME_BPCORE)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize), serde(crate = "serde_crate"))]
+pub struct WideBlindSeal<Id: SealTxid, M: SealCloseMethod = CloseMethod> {
+    /// Commitment to the specific seal close method [`CloseMethod`] which must
+    /// be used to close this seal.
+    pub method: M,
+
+    /// Txid of the seal definition, if known.
+    pub txid: Id,
+
+    /// Tx output number, which should be always known.
+    pub vout: Vout,
+
+    /// 128-bit blinding factor providing confidentiality of the seal
+    /// definition.
+    pub blinding: u128,
+}
+
+impl<Id: SealTxid, M: SealCloseMethod> WideBlindSeal<Id, M> {
+    /// Creates new seal for the provided outpoint and seal closing method. Uses
+    /// `thread_rng` to initialize blinding factor.
+    pub fn new_random(method: M, txid: impl Into<Id>, vout: impl Into<Vout>) -> Self {
+        WideBlindSeal::with_rng(method, txid, vout, &mut thread_rng())
+    }
+
+    /// Creates new seal for the provided outpoint and seal closing method. Uses
+    /// provided random number generator to create a new blinding factor.
+    pub fn with_rng(
+        method: M,
+        txid: impl Into<Id>,
+        vout: impl Into<Vout>,
+        rng: &mut impl RngCore,
+    ) -> Self {
+        let mut blinding = [0u8; 16];
+        rng.fill_bytes(&mut blinding);
+        WideBlindSeal::with_blinding(method, txid, vout, u128::from_le_bytes(blinding))
+    }
+
+    /// Reconstructs previously defined seal with a given method, transaction
+    /// id, output number and previously generated blinding factor value.
+    pub fn with_blinding(
+        method: M,
+        txid: impl Into<Id>,
+        vout: impl Into<Vout>,
+        blinding: u128,
+    ) -> Self {
+        WideBlindSeal {
+            method,
+            txid: txid.into(),
+            vout: vout.into(),
+            blinding,
+        }
+    }
+
+    /// Converts the seal into a standard [`BlindSeal`] with 64-bit blinding
+    /// factor, if the blinding value fits 64 bits.
+    pub fn to_narrow(&self) -> Option<BlindSeal<Id, M>> {
+        let blinding = u64::try_from(self.blinding).ok()?;
+        Some(BlindSeal::with_blinding(self.method, self.txid, self.vout, blinding))
+    }
+}
+
+impl<Id: SealTxid, M: SealCloseMethod> From<BlindSeal<Id, M>> for WideBlindSeal<Id, M> {
+    /// Extends blinding factor of the seal to 128 bits. Note that the concealed
+    /// form of the resulting seal differs from the concealed original seal.
+    fn from(seal: BlindSeal<Id, M>) -> Self {
+        WideBlindSeal::with_blinding(seal.method, seal.txid, seal.vout, seal.blinding as u128)
+    }
+}
+
+impl<Id: SealTxid, M: SealCloseMethod> TxoSeal<M> for WideBlindSeal<Id, M> {
+    #[inline]
+    fn method(&self) -> M { self.method }
+
+    #[inline]
+    fn txid(&self) -> Option<Txid> { self.txid.txid() }
+
+    #[inline]
+    fn vout(&self) -> Vout { self.vout }
+
+    #[inline]
+    fn outpoint(&self) -> Option<Outpoint> { self.txid.map_to_outpoint(self.vout) }
+
+    #[inline]
+    fn txid_or(&self, default_txid: Txid) -> Txid { self.txid.txid_or(default_txid) }
+
+    #[inline]
+    fn outpoint_or(&self, default_txid: Txid) -> Outpoint {
+        Outpoint::new(self.txid.txid_or(default_txid), self.vout)
+    }
+}
+
+impl<Id: SealTxid> WideBlindSeal<Id> {
+    /// Returns commitment tag used for concealing the seal. Each seal close
+    /// method has its own tag, such that the seal is bound to the method
+    /// independently of the method encoding.
+    pub fn tag(&self) -> &'static str { SecretSeal::tag_v2_wide(self.method) }
+}
+
+/// Concealment commits to the strict encoding of the seal txid, vout and
+/// blinding factor using [`WideBlindSeal::tag`].
+impl<Id: SealTxid> Conceal for WideBlindSeal<Id> {
+    type Concealed = SecretSeal;
+
+    fn conceal(&self) -> Self::Concealed {
+        let hasher = TaggedHasher::<Sha256>::new(self.tag());
+        SecretSeal::commit_v2_with(&hasher, self.txid, self.vout, self.blinding)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use amplify::hex::ToHex;
+    use commit_verify::{DigestExt, Sha256};
+
+    use super::*;
+    use crate::txout::TxPtr;
+
+    fn seal() -> WideBlindSeal<TxPtr> {
+        WideBlindSeal::with_blinding(
+            CloseMethod::TapretFirst,
+            TxPtr::WitnessTx,
+            1u32,
+            0x0123_4567_89ab_cdef_0000_0000_0000_dead,
+        )
+    }
+
+    #[test]
+    fn commitment_preimage() {
+        let mut preimage = vec![0x00, 0x01, 0x00, 0x00, 0x00];
+        preimage.extend(0x0123_4567_89ab_cdef_0000_0000_0000_dead_u128.to_le_bytes());
+        let mut hasher = Sha256::from_tag("urn:lnp-bp:lnpbp10:secret-wide-tapret1st#v2");
+        hasher.input_raw(&preimage);
+        assert_eq!(seal().conceal(), SecretSeal::from(hasher));
+    }
+
+    #[test]
+    fn commitment_vectors() {
+        let tapret = seal();
+        assert_eq!(tapret.tag(), "urn:lnp-bp:lnpbp10:secret-wide-tapret1st#v2");
+        assert_eq!(
+            tapret.conceal().to_hex(),
+            "fd4f6a7e0ccf0ef064dee4465843121939bb2fd1444764568104553c03dc838f"
+        );
+
+        let mut opret = seal();
+        opret.method = CloseMethod::OpretFirst;
+        assert_eq!(opret.tag(), "urn:lnp-bp:lnpbp10:secret-wide-opret1st#v2");
+        assert_eq!(
+            opret.conceal().to_hex(),
+            "b49a2123f8d348a209ad94355e3fcf9a37f60034f29a0be1e1f4a55c5ed3ff51"
+        );
+    }
+
+    #[test]
+    fn commitment_stability() {
+        let seal = seal();
+        assert_eq!(seal.conceal(), seal.conceal());
+
+        let mut tampered = seal;
+        tampered.blinding ^= 1 << 127;
+        assert_ne!(tampered.conceal(), seal.conceal());
+
+        let narrow = BlindSeal::<TxPtr>::with_blinded_vout(CloseMethod::TapretFirst, 1u32, 0xdead);
+        let wide = WideBlindSeal::from(narrow);
+        assert_eq!(wide.blinding, 0xdead);
+        assert_eq!(wide.to_narrow(), Some(narrow));
+        assert_ne!(wide.conceal(), narrow.conceal());
+        assert_eq!(seal.to_narrow(), None);
+    }
+
+    #[test]
+    fn random_blinding() {
+        let method = CloseMethod::OpretFirst;
+        let seal1 = WideBlindSeal::<TxPtr>::new_random(method, TxPtr::WitnessTx, 0u32);
+        let seal2 = WideBlindSeal::<TxPtr>::new_random(method, TxPtr::WitnessTx, 0u32);
+        assert_ne!(seal1.blinding, seal2.blinding);
+        assert_ne!(seal1.conceal(), seal2.conceal());
+    }
+}
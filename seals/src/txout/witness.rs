@@ -22,11 +22,11 @@
 use std::marker::PhantomData;
 
 use bc::{Tx, Txid};
-use commit_verify::mpc;
-use dbc::{DbcMethod, Method};
+use commit_verify::mpc::{self, Message, ProtocolId};
+use dbc::{Anchor, DbcMethod, Method};
 use single_use_seals::SealWitness;
 
-use crate::txout::{TxoSeal, VerifyError};
+use crate::txout::{ClosingError, TxoSeal, VerifyError};
 use crate::SealCloseMethod;
 
 /// Witness of a bitcoin-based seal being closed. Includes both transaction and
@@ -66,6 +66,11 @@ impl<Seal: TxoSeal<M>, Dbc: dbc::Proof<M>, M: SealCloseMethod> SealWitness<Seal>
     type Error = VerifyError<Dbc::Error>;
 
     fn verify_seal(&self, seal: &Seal, msg: &Self::Message) -> Result<(), Self::Error> {
+        // 0. The seal must be closed with the method used by the commitment
+        if seal.method() != self.proof.method() {
+            return Err(VerifyError::CloseMethodMismatch);
+        }
+
         // 1. The seal must match tx inputs
         let outpoint = seal.outpoint().ok_or(VerifyError::NoWitnessTxid)?;
         if !self
@@ -96,6 +101,8 @@ impl<Seal: TxoSeal<M>, Dbc: dbc::Proof<M>, M: SealCloseMethod> SealWitness<Seal>
                 if method != seal.method() {
                     return Err(VerifyError::InconsistentCloseMethod);
                 }
+            } else if seal.method() != self.proof.method() {
+                return Err(VerifyError::CloseMethodMismatch);
             } else {
                 method = Some(seal.method());
             }
@@ -116,3 +123,232 @@ impl<Seal: TxoSeal<M>, Dbc: dbc::Proof<M>, M: SealCloseMethod> SealWitness<Seal>
         self.proof.verify(msg, &self.tx).map_err(VerifyError::Dbc)
     }
 }
+
+/// Seal which was verified to be closed by a witness transaction.
+///
+/// The only way to obtain a closed seal is a successful
+/// [`verify_seal_closing`] call, thus APIs requiring proof of the seal closing
+/// may take this type instead of a bare seal definition:
+///
+/// ```compile_fail
+/// # use bc::{Outpoint, Txid};
+/// # use seals::txout::{CloseMethod, ClosedSeal, ExplicitSeal};
+/// let seal = ExplicitSeal::<Txid>::new(CloseMethod::TapretFirst, Outpoint::coinbase());
+/// let closed = ClosedSeal {
+///     seal,
+///     witness_txid: Txid::coinbase(),
+/// };
+/// ```
+#[derive(Copy, Clone, PartialEq, Eq, Hash, Debug)]
+pub struct ClosedSeal<Seal> {
+    seal: Seal,
+    witness_txid: Txid,
+}
+
+impl<Seal> ClosedSeal<Seal> {
+    /// Returns definition of the closed seal.
+    #[inline]
+    pub fn seal(&self) -> &Seal { &self.seal }
+
+    /// Returns id of the witness transaction closing the seal.
+    #[inline]
+    pub fn witness_txid(&self) -> Txid { self.witness_txid }
+
+    /// Releases seal definition, dropping the information about its closing.
+    #[inline]
+    pub fn into_seal(self) -> Seal { self.seal }
+}
+
+/// Verifies that the `seal` is closed by the `witness` over the `message`
+/// under the given protocol, returning the seal as [`ClosedSeal`].
+///
+/// Performs all the checks required to validate seal closing:
+/// 1. the `anchor` uses the same DBC proof as the `witness`;
+/// 2. the `anchor` MPC proof commits to the `message` under `protocol_id`;
+/// 3. the witness transaction spends the seal and contains DBC commitment to
+///    the resulting MPC commitment, made with the seal close method.
+pub fn verify_seal_closing<Seal, D, M>(
+    seal: &Seal,
+    witness: &Witness<D, M>,
+    anchor: &Anchor<mpc::MerkleProof, D, M>,
+    protocol_id: impl Into<ProtocolId>,
+    message: impl Into<Message>,
+) -> Result<ClosedSeal<Seal>, ClosingError<D::Error>>
+where
+    Seal: TxoSeal<M> + Clone,
+    D: dbc::Proof<M>,
+    M: SealCloseMethod,
+{
+    if anchor.method != witness.proof.method() || anchor.dbc_proof != witness.proof {
+        return Err(ClosingError::AnchorMismatch);
+    }
+    let mpc_commitment = anchor.convolve(protocol_id, message)?;
+    witness.verify_seal(seal, &mpc_commitment)?;
+    Ok(ClosedSeal {
+        seal: seal.clone(),
+        witness_txid: witness.txid,
+    })
+}
+
+#[cfg(test)]
+mod test {
+    use bc::{LockTime, Outpoint, SeqNo, TxIn, TxVer, VarIntArray};
+    use dbc::opret::OpretProof;
+
+    use super::*;
+    use crate::txout::{CloseMethod, ExplicitSeal};
+
+    #[test]
+    fn close_method_mismatch() {
+        let outpoint = Outpoint::new(Txid::from([0xA5; 32]), 0);
+        let tx = Tx {
+            version: TxVer::V2,
+            inputs: VarIntArray::try_from(vec![TxIn {
+                prev_output: outpoint,
+                sig_script: none!(),
+                sequence: SeqNo::from_consensus_u32(0xFFFFFFFF),
+                witness: none!(),
+            }])
+            .unwrap(),
+            outputs: none!(),
+            lock_time: LockTime::ZERO,
+        };
+        let witness = Witness::<OpretProof>::with(tx, OpretProof::default());
+        let msg = mpc::Commitment::from([1u8; 32]);
+
+        let tapret_seal = ExplicitSeal::<Txid>::new(CloseMethod::TapretFirst, outpoint);
+        assert!(matches!(
+            witness.verify_seal(&tapret_seal, &msg),
+            Err(VerifyError::CloseMethodMismatch)
+        ));
+        assert!(matches!(
+            witness.verify_many_seals([&tapret_seal], &msg),
+            Err(VerifyError::CloseMethodMismatch)
+        ));
+
+        // the opret seal passes the method check and fails on the missing commitment
+        let opret_seal = ExplicitSeal::<Txid>::new(CloseMethod::OpretFirst, outpoint);
+        assert!(matches!(witness.verify_seal(&opret_seal, &msg), Err(VerifyError::Dbc(_))));
+    }
+
+    #[test]
+    fn seal_witness_trait() {
+        use bc::{ScriptPubkey, TxOut};
+
+        fn is_closed<S, W: SealWitness<S>>(witness: &W, seal: &S, msg: &W::Message) -> bool {
+            witness.verify_seal(seal, msg).is_ok()
+        }
+
+        let msg = mpc::Commitment::from([0x22; 32]);
+        let outpoint = Outpoint::new(Txid::from([0xA5; 32]), 0);
+        let tx = Tx {
+            version: TxVer::V2,
+            inputs: VarIntArray::try_from(vec![TxIn {
+                prev_output: outpoint,
+                sig_script: none!(),
+                sequence: SeqNo::from_consensus_u32(0xFFFFFFFF),
+                witness: none!(),
+            }])
+            .unwrap(),
+            outputs: VarIntArray::try_from(vec![TxOut::new(
+                ScriptPubkey::op_return(&[0x22; 32]),
+                0u64,
+            )])
+            .unwrap(),
+            lock_time: LockTime::ZERO,
+        };
+        let witness = Witness::<OpretProof>::with(tx, OpretProof::default());
+
+        let seal = ExplicitSeal::<Txid>::new(CloseMethod::OpretFirst, outpoint);
+        assert!(is_closed(&witness, &seal, &msg));
+        assert!(!is_closed(&witness, &seal, &mpc::Commitment::from([0x33; 32])));
+
+        let other_seal = ExplicitSeal::<Txid>::new(
+            CloseMethod::OpretFirst,
+            Outpoint::new(Txid::from([0xA5; 32]), 1),
+        );
+        assert!(!is_closed(&witness, &other_seal, &msg));
+        let tapret_seal = ExplicitSeal::<Txid>::new(CloseMethod::TapretFirst, outpoint);
+        assert!(!is_closed(&witness, &tapret_seal, &msg));
+    }
+
+    #[test]
+    fn seal_closing_tapret() {
+        use std::str::FromStr;
+
+        use amplify::confinement::Confined;
+        use bc::{InternalPk, ScriptPubkey, TxOut};
+        use commit_verify::{CommitId, ConvolveCommit, TryCommitVerify};
+        use dbc::tapret::{TapretPathProof, TapretProof};
+
+        let protocol_id = ProtocolId::from([0x11; 32]);
+        let message = Message::from([0x22; 32]);
+        let source = mpc::MultiSource {
+            method: mpc::Method::Sha256t,
+            min_depth: 3,
+            messages: Confined::try_from_iter([(protocol_id, message)]).unwrap(),
+            static_entropy: None,
+        };
+        let tree = mpc::MerkleTree::try_commit(&source).unwrap();
+        let mpc_commitment = tree.commit_id();
+        let mpc_proof = mpc::MerkleBlock::from(tree)
+            .to_merkle_proof(protocol_id)
+            .unwrap();
+
+        let internal_pk = InternalPk::from_str(
+            "c5f93479093e2b8f724a79844cc10928dd44e9a390b539843fb83fbf842723f3",
+        )
+        .unwrap();
+        let (output_pk, tapret_proof) = internal_pk
+            .convolve_commit(&TapretPathProof::root(0), &mpc_commitment)
+            .unwrap();
+
+        let outpoint = Outpoint::new(Txid::from([0xA5; 32]), 0);
+        let tx = Tx {
+            version: TxVer::V2,
+            inputs: VarIntArray::try_from(vec![TxIn {
+                prev_output: outpoint,
+                sig_script: none!(),
+                sequence: SeqNo::from_consensus_u32(0xFFFFFFFF),
+                witness: none!(),
+            }])
+            .unwrap(),
+            outputs: VarIntArray::try_from(vec![TxOut::new(
+                ScriptPubkey::p2tr_tweaked(output_pk),
+                1000u64,
+            )])
+            .unwrap(),
+            lock_time: LockTime::ZERO,
+        };
+        let witness = Witness::<TapretProof>::with(tx, tapret_proof.clone());
+        let anchor = Anchor::new(mpc_proof, tapret_proof);
+        let seal = ExplicitSeal::<Txid>::new(CloseMethod::TapretFirst, outpoint);
+
+        let closed = verify_seal_closing(&seal, &witness, &anchor, protocol_id, message).unwrap();
+        assert_eq!(closed.seal(), &seal);
+        assert_eq!(closed.witness_txid(), witness.txid);
+        assert_eq!(closed.into_seal(), seal);
+
+        let other_message = Message::from([0x33; 32]);
+        assert!(matches!(
+            verify_seal_closing(&seal, &witness, &anchor, protocol_id, other_message),
+            Err(ClosingError::Seal(VerifyError::Dbc(_)))
+        ));
+
+        let other_seal = ExplicitSeal::<Txid>::new(
+            CloseMethod::TapretFirst,
+            Outpoint::new(Txid::from([0xA5; 32]), 1),
+        );
+        assert!(matches!(
+            verify_seal_closing(&other_seal, &witness, &anchor, protocol_id, message),
+            Err(ClosingError::Seal(VerifyError::WitnessNotClosingSeal(_)))
+        ));
+
+        let mut other_anchor = anchor.clone();
+        other_anchor.dbc_proof.path_proof = TapretPathProof::root(1);
+        assert_eq!(
+            verify_seal_closing(&seal, &witness, &other_anchor, protocol_id, message),
+            Err(ClosingError::AnchorMismatch)
+        );
+    }
+}
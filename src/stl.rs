@@ -24,9 +24,10 @@
 use bc::Txid;
 use commit_verify::mpc;
 use dbc::opret::OpretProof;
-use dbc::tapret::TapretProof;
-use dbc::{Method, LIB_NAME_BPCORE};
-use seals::txout::TxPtr;
+use dbc::sigtweak::SigtweakProof;
+use dbc::tapret::{NoCommitmentProof, TapretProof};
+use dbc::{DbcProof, Method, LIB_NAME_BPCORE};
+use seals::txout::{TxPtr, WideBlindSeal, WitnessSeal};
 use strict_types::{CompileError, LibBuilder, TypeLib};
 
 /// Strict types id for the library providing data types from [`dbc`] and
@@ -46,11 +47,19 @@ fn _bp_core_stl() -> Result<TypeLib, CompileError> {
     .transpile::<dbc::Anchor<mpc::MerkleTree, OpretProof>>()
     .transpile::<dbc::Anchor<mpc::MerkleBlock, OpretProof>>()
     .transpile::<dbc::Anchor<mpc::MerkleProof, OpretProof>>()
+    .transpile::<dbc::Anchor<mpc::MerkleTree, DbcProof>>()
+    .transpile::<dbc::Anchor<mpc::MerkleBlock, DbcProof>>()
+    .transpile::<dbc::Anchor<mpc::MerkleProof, DbcProof>>()
+    .transpile::<NoCommitmentProof>()
+    .transpile::<SigtweakProof>()
     .transpile::<seals::txout::ExplicitSeal<TxPtr, Method>>()
     .transpile::<seals::txout::ExplicitSeal<Txid, Method>>()
     .transpile::<seals::SecretSeal>()
     .transpile::<seals::txout::BlindSeal<TxPtr, Method>>()
     .transpile::<seals::txout::BlindSeal<Txid, Method>>()
+    .transpile::<WitnessSeal<Method>>()
+    .transpile::<WideBlindSeal<TxPtr, Method>>()
+    .transpile::<WideBlindSeal<Txid, Method>>()
     .compile()
 }
 